@@ -0,0 +1,127 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Most alerts kept, the oldest are dropped first.
+const MAX_ALERTS: usize = 200;
+
+/// The sorts of readings alerts are raised for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Memory,
+    Swap,
+    Disk,
+}
+
+/// How a reading compares with its thresholds, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Normal,
+    Warn,
+    Critical,
+}
+
+impl Level {
+    /// Text standing in for the level's color, for `severity_markers`.
+    pub fn marker(self) -> &'static str {
+        match self {
+            Level::Normal => "OK",
+            Level::Warn => "!",
+            Level::Critical => "!!",
+        }
+    }
+}
+
+/// A reading moving from one level to another.
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub time: DateTime<Local>,
+    pub kind: AlertKind,
+    /// What crossed, e.g. "Memory" or "Disk /home".
+    pub name: String,
+    pub level: Level,
+    /// The reading at the time, in percent.
+    pub usage: f64,
+}
+
+/// A timeline of readings crossing their warning and critical levels and
+/// coming back down, for what happened while nobody was looking.
+#[derive(Default)]
+pub struct AlertLog {
+    /// The level of each reading at its last check.
+    levels: HashMap<String, Level>,
+    /// Oldest first.
+    alerts: VecDeque<Alert>,
+}
+
+impl AlertLog {
+    /// Compares `usage` with the `warn` and `critical` levels, all in
+    /// percent, and logs an alert if `name` changed level since the last
+    /// check, returning the alert. Readings start out at the normal level.
+    pub fn check(
+        &mut self,
+        kind: AlertKind,
+        name: &str,
+        usage: f64,
+        warn: f64,
+        critical: f64,
+    ) -> Option<&Alert> {
+        let level = if usage >= critical {
+            Level::Critical
+        } else if usage >= warn {
+            Level::Warn
+        } else {
+            Level::Normal
+        };
+        let previous = self.levels.insert(name.to_string(), level);
+        if previous.unwrap_or(Level::Normal) == level {
+            return None;
+        }
+
+        if self.alerts.len() == MAX_ALERTS {
+            self.alerts.pop_front();
+        }
+        self.alerts.push_back(Alert {
+            time: Local::now(),
+            kind,
+            name: name.to_string(),
+            level,
+            usage,
+        });
+        self.alerts.back()
+    }
+
+    pub fn alerts(&self) -> &VecDeque<Alert> {
+        &self.alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_log_each_change_of_level() {
+        let mut alerts = AlertLog::default();
+        for usage in [50.0, 92.0, 95.0, 80.0, 70.0, 60.0] {
+            alerts.check(AlertKind::Memory, "Memory", usage, 75.0, 90.0);
+        }
+
+        let levels: Vec<(Level, f64)> = alerts
+            .alerts()
+            .iter()
+            .map(|alert| (alert.level, alert.usage))
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                (Level::Critical, 92.0),
+                (Level::Warn, 80.0),
+                (Level::Normal, 70.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A job run on a background thread, so slow external commands never block
+/// rendering. The UI thread polls for finished results and reads the most
+/// recent one.
+pub struct Background<T> {
+    job: Arc<dyn Fn() -> T + Send + Sync>,
+    interval: Duration,
+    tx: Sender<T>,
+    rx: Receiver<T>,
+    latest: Option<T>,
+    /// When `latest` came in.
+    received: Option<Instant>,
+    started: Option<Instant>,
+    running: bool,
+}
+
+impl<T: Send + 'static> Background<T> {
+    /// Creates a job that is re-run at most once per `interval`.
+    pub fn new(interval: Duration, job: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Background {
+            job: Arc::new(job),
+            interval,
+            tx,
+            rx,
+            latest: None,
+            received: None,
+            started: None,
+            running: false,
+        }
+    }
+
+    /// Picks up a finished result, then starts a new run if one is due.
+    /// Returns whether a new result came in.
+    pub fn poll(&mut self) -> bool {
+        let mut received = false;
+        while let Ok(value) = self.rx.try_recv() {
+            self.latest = Some(value);
+            self.received = Some(Instant::now());
+            self.running = false;
+            received = true;
+        }
+
+        let due = self
+            .started
+            .is_none_or(|started| started.elapsed() >= self.interval);
+        if !self.running && due {
+            let job = Arc::clone(&self.job);
+            let tx = self.tx.clone();
+            thread::spawn(move || {
+                // The receiver is gone once the app is shutting down
+                let _ = tx.send(job());
+            });
+            self.started = Some(Instant::now());
+            self.running = true;
+        }
+        received
+    }
+
+    /// Makes the next poll start a new run, if none is going, instead of
+    /// waiting out the interval.
+    pub fn rerun(&mut self) {
+        self.started = None;
+    }
+
+    /// Changes how often the job is re-run, taking effect from the next run.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// The most recent result, if the job has finished at least once.
+    pub fn latest(&self) -> Option<&T> {
+        self.latest.as_ref()
+    }
+
+    /// How long ago the most recent result came in.
+    pub fn age(&self) -> Option<Duration> {
+        self.received.map(|received| received.elapsed())
+    }
+
+    /// How long the current run has been going, if one is.
+    pub fn running_for(&self) -> Option<Duration> {
+        self.started
+            .filter(|_| self.running)
+            .map(|started| started.elapsed())
+    }
+
+    /// Makes the current run look like it started `ago` earlier, for tests
+    /// of how long-running jobs are shown.
+    #[cfg(test)]
+    pub fn backdate(&mut self, ago: Duration) {
+        self.started = self.started.and_then(|started| started.checked_sub(ago));
+    }
+
+    /// Whether the cached result is overdue: nothing has arrived yet, or the
+    /// current run has been going for longer than the refresh interval.
+    pub fn is_stale(&self) -> bool {
+        self.running
+            && (self.latest.is_none()
+                || self
+                    .started
+                    .is_some_and(|started| started.elapsed() >= self.interval))
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Charge and power state of a single battery.
+pub struct Battery {
+    pub name: String,
+    pub percent: f64,
+    /// As the kernel reports it: "Charging", "Discharging", "Full", ...
+    pub state: String,
+    /// Until empty while discharging, or until full while charging.
+    pub time_remaining: Option<Duration>,
+}
+
+/// Reads every battery the kernel knows about. Anything that isn't Linux,
+/// or a machine without batteries, simply has none.
+pub fn read_batteries() -> Vec<Battery> {
+    let Ok(entries) = fs::read_dir(POWER_SUPPLY_DIR) else {
+        return Vec::new();
+    };
+
+    let mut batteries: Vec<Battery> = entries
+        .flatten()
+        .filter(|entry| read(&entry.path(), "type").as_deref() == Some("Battery"))
+        .filter_map(|entry| read_battery(&entry.path()))
+        .collect();
+    batteries.sort_by(|a, b| a.name.cmp(&b.name));
+    batteries
+}
+
+fn read_battery(dir: &Path) -> Option<Battery> {
+    let name = dir.file_name()?.to_string_lossy().into_owned();
+    let state = read(dir, "status").unwrap_or_else(|| "Unknown".to_string());
+
+    // Batteries report either energy (µWh, µW) or charge (µAh, µA), the
+    // ratios work out the same for either
+    let (now, full, rate) = match read_number(dir, "energy_now") {
+        Some(now) => (
+            now,
+            read_number(dir, "energy_full"),
+            read_number(dir, "power_now"),
+        ),
+        None => (
+            read_number(dir, "charge_now")?,
+            read_number(dir, "charge_full"),
+            read_number(dir, "current_now"),
+        ),
+    };
+
+    let percent = match read_number(dir, "capacity") {
+        Some(capacity) => capacity,
+        None => now / full.filter(|full| *full > 0.0)? * 100.0,
+    };
+
+    let hours = match (state.as_str(), full, rate.filter(|rate| *rate > 0.0)) {
+        ("Discharging", _, Some(rate)) => Some(now / rate),
+        ("Charging", Some(full), Some(rate)) => Some((full - now).max(0.0) / rate),
+        _ => None,
+    };
+
+    Some(Battery {
+        name,
+        percent,
+        state,
+        time_remaining: hours.map(|hours| Duration::from_secs_f64(hours * 3600.0)),
+    })
+}
+
+fn read(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file))
+        .ok()
+        .map(|contents| contents.trim().to_string())
+}
+
+fn read_number(dir: &Path, file: &str) -> Option<f64> {
+    read(dir, file)?.parse().ok()
+}
@@ -0,0 +1,1193 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+use chrono_tz::Tz;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use glob::Pattern;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use num_format::Locale;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::alerts::AlertKind;
+use crate::format::{ByteUnits, NumberFormat};
+use crate::keys::{self, Key, KeyMapping};
+use crate::theme::{BorderStyle, Gradient, ThemeName};
+
+/// The panels that can be placed on the dashboard.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanelKind {
+    Memory,
+    Cpu,
+    Uptime,
+    Updates,
+    Programs,
+    Pueue,
+    Network,
+    Load,
+    Processes,
+    Temperatures,
+    DiskIo,
+    Errors,
+    Battery,
+    Users,
+    Docker,
+    SystemInfo,
+    Gpu,
+    Ping,
+    Ports,
+    ProcessStates,
+    Clock,
+    Logs,
+    Alerts,
+    Hosts,
+    Health,
+    MemoryPressure,
+    /// One of the `command_panels`, by title, written `{ command = "title" }`.
+    Command(String),
+}
+
+impl PanelKind {
+    /// Short name for the panel in lists such as the help overlay.
+    pub fn label(&self) -> &str {
+        match self {
+            PanelKind::Memory => "Memory and disks",
+            PanelKind::Cpu => "CPU",
+            PanelKind::Uptime => "Uptime",
+            PanelKind::Updates => "Updates",
+            PanelKind::Programs => "Programs",
+            PanelKind::Pueue => "Pueue",
+            PanelKind::Network => "Network",
+            PanelKind::Load => "Load average",
+            PanelKind::Processes => "Top processes",
+            PanelKind::Temperatures => "Temperatures",
+            PanelKind::DiskIo => "Disk I/O",
+            PanelKind::Errors => "Errors",
+            PanelKind::Battery => "Battery",
+            PanelKind::Users => "Users",
+            PanelKind::Docker => "Docker",
+            PanelKind::SystemInfo => "System info",
+            PanelKind::Gpu => "GPUs",
+            PanelKind::Ping => "Ping",
+            PanelKind::Ports => "Listening ports",
+            PanelKind::ProcessStates => "Process states",
+            PanelKind::Clock => "Clock",
+            PanelKind::Logs => "Log tail",
+            PanelKind::Alerts => "Alerts",
+            PanelKind::Hosts => "Remote hosts",
+            PanelKind::Health => "Health",
+            PanelKind::MemoryPressure => "Memory pressure",
+            PanelKind::Command(title) => title,
+        }
+    }
+}
+
+/// Ways of checking on the configured programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgramCheck {
+    /// Look for a running process with exactly that name.
+    Process,
+    /// Ask `systemctl is-active` about the unit with that name.
+    Systemd,
+}
+
+/// How the CPU panel shows each core.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuCoreView {
+    /// A line per core, with a usage bar and the clock speed.
+    List,
+    /// A bar chart, one bar per core, wrapped onto more rows as needed.
+    Bars,
+}
+
+/// Where alerts get posted to, as JSON.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Webhook {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// The kinds of alerts to post, all of them by default.
+    #[serde(default = "all_alert_kinds")]
+    pub alerts: Vec<AlertKind>,
+}
+
+fn all_alert_kinds() -> Vec<AlertKind> {
+    vec![AlertKind::Memory, AlertKind::Swap, AlertKind::Disk]
+}
+
+/// The shape of the JSON posted to a webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The alert's fields: host, time, kind, metric, level and value.
+    #[default]
+    Generic,
+    /// A message in `text`, as Slack's incoming webhooks take it.
+    Slack,
+}
+
+/// Where the log tail panel reads from, written `{ unit = "nginx" }` or
+/// `{ file = "/var/log/syslog" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSource {
+    /// A systemd unit's journal, through `journalctl`.
+    Unit(String),
+    File(PathBuf),
+}
+
+/// A machine the hosts panel reads metrics from over SSH.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteHost {
+    /// What the hosts panel calls the host.
+    pub name: String,
+    /// Where `ssh` connects to, e.g. `web1.example.com` or `admin@10.0.0.5`,
+    /// the name when unset.
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    /// Private key to log in with, instead of the agent's or ssh's defaults.
+    pub identity_file: Option<PathBuf>,
+}
+
+impl RemoteHost {
+    /// The arguments that make `ssh` connect to the host, without ever
+    /// stopping to ask for a password or about an unknown host key.
+    pub fn ssh_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-o".to_string(),
+            "ConnectTimeout=5".to_string(),
+        ];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(identity_file) = &self.identity_file {
+            args.extend(["-i".to_string(), identity_file.display().to_string()]);
+        }
+        args.push(self.address.clone().unwrap_or_else(|| self.name.clone()));
+        args
+    }
+}
+
+/// A panel showing the output of a shell command, re-run on its own interval.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CommandPanel {
+    pub title: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(
+        default = "default_command_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+    /// Commands run by pressing a key while the panel has focus.
+    #[serde(default)]
+    pub actions: Vec<PanelAction>,
+}
+
+/// A command a command panel runs when its key is pressed, with the output
+/// shown in an overlay, written
+/// `{ key = "u", command = "apt", args = ["upgrade", "-y"], destructive = true }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PanelAction {
+    pub key: char,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Ask for confirmation before running the command.
+    #[serde(default)]
+    pub destructive: bool,
+}
+
+impl PanelAction {
+    /// The command with its arguments, as it would be typed in a shell.
+    pub fn command_line(&self) -> String {
+        let mut line = self.command.clone();
+        for arg in &self.args {
+            line.push(' ');
+            line.push_str(arg);
+        }
+        line
+    }
+}
+
+fn default_command_interval() -> Duration {
+    Duration::from_secs(10)
+}
+
+/// User configuration, read from `~/.config/rashboard/config.toml`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Refresh interval, in milliseconds or as a string with a unit suffix.
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub tick_rate: Duration,
+    /// Panels to show, top to bottom.
+    pub panels: Vec<PanelKind>,
+    /// Arrange the panels in a grid this many columns wide, filled left to
+    /// right and then top to bottom. When unset, panels are stacked and only
+    /// split into two columns once they no longer fit.
+    pub columns: Option<u16>,
+    /// Process names, or systemd units, reported on by the program status panel.
+    pub programs: Vec<String>,
+    /// How the program status panel decides whether a program is up.
+    pub program_check: ProgramCheck,
+    /// Also list pseudo filesystems such as `tmpfs` in the disk panel.
+    pub show_pseudo_filesystems: bool,
+    /// Mount points to list in the disk panel, as glob patterns such as
+    /// "/mnt/*". Every one is listed when this is empty.
+    #[serde(deserialize_with = "deserialize_patterns")]
+    pub disk_include: Vec<Pattern>,
+    /// Mount points to leave out of the disk panel, as glob patterns.
+    #[serde(deserialize_with = "deserialize_patterns")]
+    pub disk_exclude: Vec<Pattern>,
+    /// Show sizes in binary (KiB, MiB) or decimal (KB, MB) units.
+    pub byte_units: ByteUnits,
+    /// Units for single panels instead of `byte_units`, keyed by panel name,
+    /// e.g. `{ disk_io = "decimal" }`.
+    pub panel_byte_units: HashMap<PanelKind, ByteUnits>,
+    /// Units for filesystem sizes in the memory panel, which are usually
+    /// sold in decimal ones. The memory panel's units when unset.
+    pub disk_byte_units: Option<ByteUnits>,
+    /// Locale whose thousands separators to group large numbers with, e.g.
+    /// "en" for 1,048,576 or "de" for 1.048.576. Not grouped when unset.
+    pub number_locale: Option<Locale>,
+    /// Built-in color theme.
+    pub theme: ThemeName,
+    /// Mark readings OK, ! or !! by their thresholds, so the level can be
+    /// told without telling the colors apart.
+    pub severity_markers: bool,
+    /// Frame around the panels: plain, rounded, thick, double or none.
+    pub border_style: BorderStyle,
+    /// Frames for single panels instead of `border_style`, keyed by panel
+    /// name, e.g. `{ memory = "double" }`.
+    pub panel_borders: HashMap<PanelKind, BorderStyle>,
+    /// Titles for single panels instead of their usual ones, keyed by panel
+    /// name, e.g. `{ programs = "Critical Services" }`.
+    pub panel_titles: HashMap<PanelKind, String>,
+    /// Draw usage gauges as plain text, for terminals that render block
+    /// characters poorly.
+    pub text_gauges: bool,
+    /// Color gauges along a smooth gradient: always, never, or when the
+    /// terminal has 24-bit color. Never with the color blind theme, whose gauges
+    /// keep its three colors.
+    pub gauge_gradient: Gradient,
+    /// Show the CPU panel's cores as a list or as a bar chart, which fits
+    /// many more of them.
+    pub cpu_cores: CpuCoreView,
+    /// Draw sparklines, bars and scrollbars with ASCII characters instead of
+    /// Unicode blocks.
+    pub ascii: bool,
+    /// Usage levels at which gauges turn yellow and red.
+    pub thresholds: Thresholds,
+    /// Send a desktop notification when memory or a disk turns critical, and
+    /// again once it recovers.
+    pub notifications: bool,
+    /// Pueue groups shown in the pueue panel, in order.
+    pub pueue_groups: Vec<String>,
+    /// Number of samples kept for the CPU and memory sparklines and the
+    /// network chart.
+    pub history_length: usize,
+    /// Number of processes listed in the top processes panel.
+    pub top_processes: usize,
+    /// Leave the battery panel out on machines without a battery.
+    pub hide_missing_battery: bool,
+    /// Hide the loopback interface in the network panel.
+    pub exclude_loopback: bool,
+    /// Only list Docker containers whose name contains this.
+    pub docker_filter: Option<String>,
+    /// List the upgradable packages in the updates panel, rather than only
+    /// counting them.
+    pub list_updates: bool,
+    /// Hosts, by name or address, watched by the ping panel.
+    pub ping_hosts: Vec<String>,
+    /// Only list these ports in the listening ports panel, all when empty.
+    pub ports: Vec<u16>,
+    /// Time zone of the clock panel, e.g. "UTC" or "Asia/Tokyo", the local
+    /// time zone when unset.
+    pub clock_timezone: Option<Tz>,
+    /// How the clock panel shows the time, in strftime syntax.
+    pub clock_format: String,
+    /// What the log tail panel shows the end of.
+    pub log_source: Option<LogSource>,
+    /// Number of lines the log tail panel keeps, at most 65535.
+    pub log_lines: usize,
+    /// Post memory, swap and disk alerts to a webhook, written
+    /// `{ url = "https://…", format = "slack", alerts = ["disk"] }`.
+    pub webhook: Option<Webhook>,
+    /// Key that exits the dashboard.
+    pub quit_key: char,
+    /// Also exit on `Ctrl-C`.
+    pub quit_on_ctrl_c: bool,
+    /// Also exit on `Esc`.
+    pub quit_on_esc: bool,
+    /// Require the quit key to be pressed twice in a row.
+    pub confirm_quit: bool,
+    /// Keys to bind to other actions than by default, or to none, e.g.
+    /// `{ x = "kill_process", K = "none", "ctrl-n" = "focus_next" }`.
+    pub keys: HashMap<Key, KeyMapping>,
+    /// Come back with the panels arranged, hidden and focused as they were
+    /// left, and the same sort order and usage format, instead of as
+    /// configured.
+    pub remember_ui_state: bool,
+    /// Custom panels that show a command's output.
+    pub command_panels: Vec<CommandPanel>,
+    /// Machines the hosts panel watches over SSH.
+    pub hosts: Vec<RemoteHost>,
+    /// How often each panel fetches new data. The screen is still redrawn
+    /// every tick, from the last data fetched.
+    pub intervals: Intervals,
+    /// Where `--headless` appends its readings, by default
+    /// `$XDG_STATE_HOME/rashboard/metrics.jsonl`.
+    pub log_file: Option<PathBuf>,
+    /// Size in bytes past which the headless log is rotated, 0 for never.
+    pub max_log_size: u64,
+    /// Named alternatives to the panels, programs and thresholds above,
+    /// written as `[profiles.NAME]` tables and picked with `--profile`.
+    pub profiles: HashMap<String, Profile>,
+    /// Profile used when `--profile` isn't given.
+    pub default_profile: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            tick_rate: Duration::from_secs(1),
+            panels: vec![
+                PanelKind::Memory,
+                PanelKind::Cpu,
+                PanelKind::Load,
+                PanelKind::Network,
+                PanelKind::Uptime,
+                PanelKind::Updates,
+                PanelKind::Programs,
+                PanelKind::Pueue,
+            ],
+            columns: None,
+            programs: vec!["nginx".to_string(), "mysql".to_string()],
+            program_check: ProgramCheck::Process,
+            show_pseudo_filesystems: false,
+            disk_include: Vec::new(),
+            disk_exclude: Vec::new(),
+            byte_units: ByteUnits::Binary,
+            panel_byte_units: HashMap::new(),
+            disk_byte_units: None,
+            number_locale: None,
+            theme: ThemeName::Dark,
+            severity_markers: false,
+            border_style: BorderStyle::Plain,
+            panel_borders: HashMap::new(),
+            panel_titles: HashMap::new(),
+            text_gauges: false,
+            gauge_gradient: Gradient::Auto,
+            cpu_cores: CpuCoreView::List,
+            ascii: false,
+            thresholds: Thresholds::default(),
+            notifications: false,
+            pueue_groups: vec!["SERVICES".to_string()],
+            history_length: 60,
+            top_processes: 5,
+            hide_missing_battery: true,
+            exclude_loopback: true,
+            docker_filter: None,
+            list_updates: false,
+            ping_hosts: Vec::new(),
+            ports: Vec::new(),
+            clock_timezone: None,
+            clock_format: "%A %Y-%m-%d %H:%M:%S".to_string(),
+            log_source: None,
+            log_lines: 100,
+            webhook: None,
+            quit_key: 'q',
+            quit_on_ctrl_c: true,
+            quit_on_esc: false,
+            confirm_quit: false,
+            keys: HashMap::new(),
+            remember_ui_state: true,
+            command_panels: Vec::new(),
+            hosts: Vec::new(),
+            intervals: Intervals::default(),
+            log_file: None,
+            max_log_size: 10 * 1024 * 1024,
+            profiles: HashMap::new(),
+            default_profile: None,
+        }
+    }
+}
+
+/// Settings for one purpose of the dashboard, such as a web or a database
+/// server. Whatever a profile leaves out stays as set at the top level,
+/// apart from single thresholds: a `thresholds` table in a profile replaces
+/// them all, with the defaults for levels it doesn't set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub panels: Option<Vec<PanelKind>>,
+    pub programs: Option<Vec<String>>,
+    pub thresholds: Option<Thresholds>,
+}
+
+/// Warning and critical levels, as percentages.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    pub memory_warn: f64,
+    pub memory_critical: f64,
+    pub swap_warn: f64,
+    pub swap_critical: f64,
+    /// Per filesystem, for the space in use.
+    pub disk_warn: f64,
+    pub disk_critical: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            memory_warn: 75.0,
+            memory_critical: 90.0,
+            swap_warn: 25.0,
+            swap_critical: 50.0,
+            disk_warn: 80.0,
+            disk_critical: 95.0,
+        }
+    }
+}
+
+/// Per-panel refresh intervals. Panels left unset refresh every tick.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Intervals {
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub memory: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub cpu: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub network: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub disk_io: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub temperatures: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub programs: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub processes: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub updates: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub pueue: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub battery: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub users: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub docker: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub gpu: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub ping: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub ports: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub process_states: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub logs: Option<Duration>,
+    #[serde(deserialize_with = "deserialize_some_duration")]
+    pub hosts: Option<Duration>,
+}
+
+impl Default for Intervals {
+    fn default() -> Self {
+        Intervals {
+            memory: None,
+            cpu: None,
+            network: None,
+            disk_io: None,
+            temperatures: None,
+            programs: None,
+            processes: None,
+            // Package lists only change when the mirrors sync
+            updates: Some(Duration::from_secs(15 * 60)),
+            pueue: None,
+            battery: None,
+            users: None,
+            docker: None,
+            gpu: None,
+            // A ping per host every tick would be a lot of traffic for little news
+            ping: Some(Duration::from_secs(5)),
+            ports: None,
+            process_states: None,
+            logs: None,
+            // Every host is a new SSH login
+            hosts: Some(Duration::from_secs(10)),
+        }
+    }
+}
+
+impl Intervals {
+    /// The interval configured for a panel, `None` meaning every tick.
+    pub fn get(&self, panel: &PanelKind) -> Option<Duration> {
+        match panel {
+            PanelKind::Memory => self.memory,
+            PanelKind::Cpu => self.cpu,
+            PanelKind::Network => self.network,
+            PanelKind::DiskIo => self.disk_io,
+            PanelKind::Temperatures => self.temperatures,
+            PanelKind::Programs => self.programs,
+            PanelKind::Processes => self.processes,
+            PanelKind::Updates => self.updates,
+            PanelKind::Pueue => self.pueue,
+            PanelKind::Battery => self.battery,
+            PanelKind::Users => self.users,
+            PanelKind::Docker => self.docker,
+            PanelKind::Gpu => self.gpu,
+            PanelKind::Ping => self.ping,
+            PanelKind::Ports => self.ports,
+            PanelKind::ProcessStates => self.process_states,
+            PanelKind::Logs => self.logs,
+            PanelKind::Hosts => self.hosts,
+            // Read once at startup
+            PanelKind::SystemInfo => None,
+            // Nothing to fetch, or refreshed on their own schedule
+            PanelKind::Uptime
+            | PanelKind::Load
+            | PanelKind::Errors
+            | PanelKind::Alerts
+            | PanelKind::Clock
+            | PanelKind::Health
+            | PanelKind::MemoryPressure
+            | PanelKind::Command(_) => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// Every problem `Config::validate` found, one message each.
+    Invalid(PathBuf, Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "failed to read {}: {}", path.display(), err),
+            ConfigError::Parse(path, err) => {
+                let err = err.to_string();
+                write!(f, "invalid config {}:\n{}", path.display(), err.trim_end())?;
+                match suggestion(&err) {
+                    Some(suggestion) => write!(f, "\n{}", suggestion),
+                    None => Ok(()),
+                }
+            }
+            ConfigError::Invalid(path, problems) => match problems.as_slice() {
+                [problem] => write!(f, "invalid config {}: {}", path.display(), problem),
+                problems => {
+                    write!(f, "invalid config {}:", path.display())?;
+                    for problem in problems {
+                        write!(f, "\n  - {}", problem)?;
+                    }
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ConfigError {
+    /// The error on a single line, for the footer.
+    pub fn summary(&self) -> String {
+        match self {
+            ConfigError::Io(..) => self.to_string(),
+            ConfigError::Parse(path, err) => {
+                let message = err.message().trim_end();
+                match suggestion(message) {
+                    Some(suggestion) => {
+                        format!(
+                            "invalid config {}: {} ({})",
+                            path.display(),
+                            message,
+                            suggestion
+                        )
+                    }
+                    None => format!("invalid config {}: {}", path.display(), message),
+                }
+            }
+            ConfigError::Invalid(path, problems) => {
+                format!("invalid config {}: {}", path.display(), problems.join("; "))
+            }
+        }
+    }
+}
+
+/// A "did you mean" for serde's errors about unknown fields and variants,
+/// which list everything that was expected but leave spotting the typo to
+/// the reader.
+fn suggestion(message: &str) -> Option<String> {
+    let (_, rest) = message
+        .split_once("unknown field `")
+        .or_else(|| message.split_once("unknown variant `"))?;
+    let (unknown, expected) = rest.split_once('`')?;
+    let expected = expected.lines().next()?.split('`').skip(1).step_by(2);
+    closest(unknown, expected).map(|name| format!("did you mean `{}`?", name))
+}
+
+/// The one of `names` that `name` is most likely a typo of, if any is close.
+fn closest<'a>(name: &str, names: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    names
+        .into_iter()
+        .map(|candidate| {
+            (
+                strsim::normalized_damerau_levenshtein(name, candidate),
+                candidate,
+            )
+        })
+        .filter(|(similarity, _)| *similarity > 0.6)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate)
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults if there is none,
+    /// with `profile` or else the default profile applied.
+    pub fn load(profile: Option<&str>) -> Result<Config, ConfigError> {
+        let Some(path) = default_path() else {
+            return Ok(Config::default());
+        };
+
+        let mut config: Config = match fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(err) => return Err(ConfigError::Parse(path, err)),
+            },
+            // Without a file, only asking for a profile can go wrong
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(err) => return Err(ConfigError::Io(path, err)),
+        };
+
+        if let Some(name) = profile
+            .map(str::to_string)
+            .or(config.default_profile.take())
+        {
+            config
+                .apply_profile(&name)
+                .map_err(|message| ConfigError::Invalid(path.clone(), vec![message]))?;
+        }
+        config
+            .validate()
+            .map_err(|problems| ConfigError::Invalid(path, problems))?;
+        Ok(config)
+    }
+
+    /// Replaces the top-level settings with those the profile sets.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let Some(profile) = self.profiles.remove(name) else {
+            return Err(
+                match closest(name, self.profiles.keys().map(String::as_str)) {
+                    Some(closest) => {
+                        format!("no profile named `{}`, did you mean `{}`?", name, closest)
+                    }
+                    None if self.profiles.is_empty() => format!(
+                        "no profile named `{}`, add one as a `[profiles.{}]` table",
+                        name, name
+                    ),
+                    None => format!("no profile named `{}`", name),
+                },
+            );
+        };
+        if let Some(panels) = profile.panels {
+            self.panels = panels;
+        }
+        if let Some(programs) = profile.programs {
+            self.programs = programs;
+        }
+        if let Some(thresholds) = profile.thresholds {
+            self.thresholds = thresholds;
+        }
+        Ok(())
+    }
+
+    /// Checks what the types alone can't, such as panels referring to
+    /// command panels that exist. Every problem found is reported, each
+    /// naming the setting at fault and how to fix it.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if self.panels.is_empty() {
+            problems.push(
+                "`panels` is empty, list at least one, e.g. `panels = [\"memory\", \"cpu\"]`"
+                    .to_string(),
+            );
+        }
+        if self.columns == Some(0) {
+            problems
+                .push("`columns` must be at least 1, or left out to stack the panels".to_string());
+        }
+
+        // The panel scrolls by line, with a u16 offset
+        if self.log_lines > u16::MAX as usize {
+            problems.push(format!(
+                "`log_lines` is {}, it can be at most {}",
+                self.log_lines,
+                u16::MAX
+            ));
+        }
+
+        for panel in &self.panels {
+            if let PanelKind::Command(title) = panel {
+                if self.command_panel(title).is_none() {
+                    let titles = self.command_panels.iter().map(|panel| panel.title.as_str());
+                    problems.push(match closest(title, titles) {
+                        Some(closest) => format!(
+                            "`panels` has a command panel `{}` that isn't in `command_panels`, did you mean `{}`?",
+                            title, closest
+                        ),
+                        None => format!(
+                            "`panels` has a command panel `{}` that isn't in `command_panels`, add one with that title",
+                            title
+                        ),
+                    });
+                }
+            }
+        }
+        let bindings = keys::bindings(self);
+        for (i, panel) in self.command_panels.iter().enumerate() {
+            if self.command_panels[..i]
+                .iter()
+                .any(|other| other.title == panel.title)
+            {
+                problems.push(format!(
+                    "`command_panels` has more than one panel titled `{}`, give each its own title",
+                    panel.title
+                ));
+            }
+            if panel.command.trim().is_empty() {
+                problems.push(format!(
+                    "command panel `{}` has an empty `command`",
+                    panel.title
+                ));
+            }
+            for (j, action) in panel.actions.iter().enumerate() {
+                // Panel actions are looked up first, so they would hide these
+                let key = KeyEvent::new(KeyCode::Char(action.key), KeyModifiers::NONE);
+                if let Some(bound) = keys::action_for(&bindings, &key) {
+                    let description = bound.description();
+                    problems.push(format!(
+                        "command panel `{}` has an action on `{}`, the key to {}{}, pick another key",
+                        panel.title,
+                        action.key,
+                        description[..1].to_lowercase(),
+                        &description[1..]
+                    ));
+                } else if panel.actions[..j]
+                    .iter()
+                    .any(|other| other.key == action.key)
+                {
+                    problems.push(format!(
+                        "command panel `{}` has more than one action on `{}`",
+                        panel.title, action.key
+                    ));
+                }
+                if action.command.trim().is_empty() {
+                    problems.push(format!(
+                        "command panel `{}` has an action on `{}` with an empty `command`",
+                        panel.title, action.key
+                    ));
+                }
+            }
+        }
+
+        if self.panels.contains(&PanelKind::Programs) && self.programs.is_empty() {
+            problems.push(
+                "`programs` is empty but the programs panel is shown, list some programs or take `programs` out of `panels`"
+                    .to_string(),
+            );
+        }
+        if self.panels.contains(&PanelKind::Pueue) && self.pueue_groups.is_empty() {
+            problems.push(
+                "`pueue_groups` is empty but the pueue panel is shown, list some groups, such as \"default\", or take `pueue` out of `panels`"
+                    .to_string(),
+            );
+        }
+        if self.panels.contains(&PanelKind::Hosts) && self.hosts.is_empty() {
+            problems.push(
+                "`hosts` is empty but the hosts panel is shown, add a `[[hosts]]` table per host or take `hosts` out of `panels`"
+                    .to_string(),
+            );
+        }
+        for (i, host) in self.hosts.iter().enumerate() {
+            if self.hosts[..i].iter().any(|other| other.name == host.name) {
+                problems.push(format!(
+                    "`hosts` has more than one host named `{}`, give each its own name",
+                    host.name
+                ));
+            }
+        }
+
+        let thresholds = &self.thresholds;
+        for (name, warn, critical) in [
+            ("memory", thresholds.memory_warn, thresholds.memory_critical),
+            ("swap", thresholds.swap_warn, thresholds.swap_critical),
+            ("disk", thresholds.disk_warn, thresholds.disk_critical),
+        ] {
+            for (level, value) in [("warn", warn), ("critical", critical)] {
+                if !(0.0..=100.0).contains(&value) {
+                    problems.push(format!(
+                        "`thresholds.{}_{}` is {}, it must be a percentage from 0 to 100",
+                        name, level, value
+                    ));
+                }
+            }
+            if warn > critical {
+                problems.push(format!(
+                    "`thresholds.{0}_warn` ({1}) is above `thresholds.{0}_critical` ({2}), the warning level must be the lower one",
+                    name, warn, critical
+                ));
+            }
+        }
+
+        if let Some(webhook) = &self.webhook {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                problems.push(format!(
+                    "`webhook.url` is `{}`, it must start with http:// or https://",
+                    webhook.url
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Whether the disk mounted at `mount_point` passes `disk_include` and
+    /// `disk_exclude`.
+    pub fn shows_mount(&self, mount_point: &str) -> bool {
+        let included = self.disk_include.is_empty()
+            || self
+                .disk_include
+                .iter()
+                .any(|pattern| pattern.matches(mount_point));
+        included
+            && !self
+                .disk_exclude
+                .iter()
+                .any(|pattern| pattern.matches(mount_point))
+    }
+
+    /// The index into `command_panels` of the panel with this title.
+    pub fn command_panel(&self, title: &str) -> Option<usize> {
+        self.command_panels
+            .iter()
+            .position(|panel| panel.title == title)
+    }
+}
+
+/// Lower bound for the refresh interval, anything faster just burns CPU.
+pub const MIN_TICK_RATE: Duration = Duration::from_millis(100);
+
+/// Parses a duration, treating a bare number as milliseconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("`{}` is not a valid duration", value))?;
+    let duration = match unit {
+        "" | "ms" => Duration::from_millis(number),
+        "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(
+            number
+                .checked_mul(60)
+                .ok_or_else(|| format!("`{}` is too long", value))?,
+        ),
+        _ => return Err(format!("unknown unit `{}`, expected ms, s or m", unit)),
+    };
+
+    if duration < MIN_TICK_RATE {
+        return Err(format!(
+            "`{}` is too short, it must be at least {}ms",
+            value,
+            MIN_TICK_RATE.as_millis()
+        ));
+    }
+
+    Ok(duration)
+}
+
+/// Accepts either a number of milliseconds or a string like `"2s"`.
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Millis(u64),
+        Text(String),
+    }
+
+    let text = match Raw::deserialize(deserializer)? {
+        Raw::Millis(millis) => millis.to_string(),
+        Raw::Text(text) => text,
+    };
+    parse_duration(&text).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_some_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    deserialize_duration(deserializer).map(Some)
+}
+
+fn deserialize_patterns<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Pattern>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern)
+                .map_err(|err| serde::de::Error::custom(format!("`{}`: {}", pattern, err)))
+        })
+        .collect()
+}
+
+/// Watches the config file for changes. The parent directory is watched
+/// rather than the file itself, since editors usually save by replacing it.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    rx: Receiver<notify::Result<notify::Event>>,
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Starts watching, or returns `None` if there is no config directory
+    /// to watch.
+    pub fn new() -> Option<ConfigWatcher> {
+        let path = default_path()?;
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).ok()?;
+        watcher
+            .watch(path.parent()?, RecursiveMode::NonRecursive)
+            .ok()?;
+
+        Some(ConfigWatcher {
+            path,
+            rx,
+            _watcher: watcher,
+        })
+    }
+
+    /// Whether the config file changed since the last call.
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        while let Ok(event) = self.rx.try_recv() {
+            changed |= event
+                .is_ok_and(|event| !event.kind.is_access() && event.paths.contains(&self.path));
+        }
+        changed
+    }
+}
+
+impl Config {
+    /// How numbers on the dashboard are formatted.
+    pub fn number_format(&self) -> NumberFormat {
+        NumberFormat {
+            byte_units: self.byte_units,
+            locale: self.number_locale,
+        }
+    }
+
+    /// How numbers in `panel` are formatted, with its own byte units if set.
+    pub fn panel_number_format(&self, panel: &PanelKind) -> NumberFormat {
+        NumberFormat {
+            byte_units: self
+                .panel_byte_units
+                .get(panel)
+                .copied()
+                .unwrap_or(self.byte_units),
+            ..self.number_format()
+        }
+    }
+
+    /// The headless log file, as configured or the default.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        self.log_file
+            .clone()
+            .or_else(|| Some(state_dir()?.join("metrics.jsonl")))
+    }
+}
+
+/// The defaults as a config file, with every setting explained, for
+/// `--init-config` to start users off with.
+pub const DEFAULT_CONFIG: &str = include_str!("default_config.toml");
+
+/// Writes `DEFAULT_CONFIG` to where the config is read from, returning that
+/// path. A config that is already there is only replaced with `force`.
+pub fn init(force: bool) -> Result<PathBuf, String> {
+    let path = default_path().ok_or("no home directory to put the config in")?;
+    if !force && path.exists() {
+        return Err(format!(
+            "{} already exists, pass --force to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("failed to create {}: {}", parent.display(), err))?;
+    }
+    fs::write(&path, DEFAULT_CONFIG)
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+    Ok(path)
+}
+
+/// `$XDG_CONFIG_HOME/rashboard/config.toml`, or `~/.config/rashboard/config.toml`.
+fn default_path() -> Option<PathBuf> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("rashboard").join("config.toml"))
+}
+
+/// `$XDG_STATE_HOME/rashboard`, or `~/.local/state/rashboard`, for what
+/// rashboard writes that isn't config.
+pub fn state_dir() -> Option<PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+
+    Some(state_dir.join("rashboard"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_are_parsed_with_their_unit() {
+        assert_eq!(parse_duration("500"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::from_secs(900)));
+        assert!(parse_duration("10ms").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert_eq!(
+            parse_duration("999999999999999999m"),
+            Err("`999999999999999999m` is too long".to_string())
+        );
+    }
+
+    #[test]
+    fn log_lines_must_fit_the_scroll_offset() {
+        let config = Config {
+            log_lines: 100_000,
+            ..Config::default()
+        };
+        assert_eq!(
+            config.validate(),
+            Err(vec![
+                "`log_lines` is 100000, it can be at most 65535".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn profiles_replace_what_they_set() {
+        let mut config: Config = toml::from_str(
+            r#"
+            programs = ["nginx"]
+            [profiles.db]
+            panels = ["memory", "processes"]
+            "#,
+        )
+        .unwrap();
+
+        config.apply_profile("db").unwrap();
+        assert_eq!(config.panels, [PanelKind::Memory, PanelKind::Processes]);
+        assert_eq!(config.programs, ["nginx"]);
+        assert!(config.apply_profile("web").is_err());
+    }
+
+    #[test]
+    fn config_problems_are_all_reported() {
+        let config: Config = toml::from_str(
+            r#"
+            panels = ["programs"]
+            programs = []
+
+            [thresholds]
+            memory_warn = 95
+            "#,
+        )
+        .unwrap();
+        let problems = config.validate().unwrap_err();
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].starts_with("`programs` is empty"));
+        assert!(problems[1].starts_with("`thresholds.memory_warn` (95) is above"));
+
+        // Panel actions would hide any global binding, not just quitting
+        let config: Config = toml::from_str(
+            r#"
+            keys = { x = "kill_process" }
+
+            [[command_panels]]
+            title = "Jobs"
+            command = "true"
+            actions = [
+                { key = "q", command = "true" },
+                { key = "?", command = "true" },
+                { key = "x", command = "true" },
+                { key = "u", command = "true" },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.validate().unwrap_err(),
+            [
+                "command panel `Jobs` has an action on `q`, the key to quit, pick another key",
+                "command panel `Jobs` has an action on `?`, the key to show or hide this help, \
+                 pick another key",
+                "command panel `Jobs` has an action on `x`, the key to send SIGTERM to the \
+                 selected process, pick another key",
+            ]
+        );
+
+        let err = toml::from_str::<Config>("theme = \"drak\"").unwrap_err();
+        let err = ConfigError::Parse(PathBuf::from("config.toml"), err);
+        assert!(
+            err.summary().ends_with("(did you mean `dark`?)"),
+            "{}",
+            err.summary()
+        );
+    }
+
+    #[test]
+    fn default_config_file_matches_the_defaults() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(format!("{:?}", config), format!("{:?}", Config::default()));
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Local};
+
+/// Most errors kept, the oldest are dropped first.
+const MAX_ERRORS: usize = 50;
+
+/// A non-fatal error, along with how often and when it happened.
+pub struct LoggedError {
+    /// What failed, e.g. the panel or command name.
+    pub source: String,
+    pub message: String,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub count: usize,
+}
+
+/// Collects errors from panels that fail without taking the dashboard down,
+/// so they can be shown rather than silently swallowed. Repeats of the same
+/// error are folded into one entry.
+#[derive(Default)]
+pub struct ErrorLog {
+    /// Ordered by when each error was last seen, most recent last.
+    entries: Vec<LoggedError>,
+}
+
+impl ErrorLog {
+    pub fn record(&mut self, source: &str, message: &str) {
+        let now = Local::now();
+        let existing = self
+            .entries
+            .iter()
+            .position(|entry| entry.source == source && entry.message == message);
+
+        let entry = match existing {
+            Some(index) => {
+                let mut entry = self.entries.remove(index);
+                entry.last_seen = now;
+                entry.count += 1;
+                entry
+            }
+            None => LoggedError {
+                source: source.to_string(),
+                message: message.to_string(),
+                first_seen: now,
+                last_seen: now,
+                count: 1,
+            },
+        };
+        self.entries.push(entry);
+
+        if self.entries.len() > MAX_ERRORS {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn entries(&self) -> &[LoggedError] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
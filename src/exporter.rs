@@ -0,0 +1,274 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::metrics::InodeStats;
+use crate::snapshot::Snapshot;
+
+/// How often the server thread checks whether it should stop.
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a client gets to send its request before it is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Serves the latest snapshot in the Prometheus text format on `/metrics`.
+/// The server thread is stopped and joined when this is dropped.
+pub struct MetricsServer {
+    metrics: Arc<Mutex<String>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MetricsServer {
+    /// Listens on `address`, answering each client on a thread of its own
+    /// so a slow one doesn't hold up the rest.
+    pub fn start(address: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        // Non-blocking, so the thread gets to notice it should stop
+        listener.set_nonblocking(true)?;
+
+        let metrics = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let metrics = Arc::clone(&metrics);
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let metrics = Arc::clone(&metrics);
+                            // A client going away mid-request is its problem
+                            thread::spawn(move || respond(stream, &metrics));
+                        }
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                            thread::sleep(ACCEPT_INTERVAL);
+                        }
+                        Err(_) => thread::sleep(ACCEPT_INTERVAL),
+                    }
+                }
+            })
+        };
+
+        Ok(MetricsServer {
+            metrics,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Replaces what is served with the readings in `snapshot`.
+    pub fn update(&self, snapshot: &Snapshot) {
+        let text = exposition(snapshot);
+        *self.metrics.lock().unwrap() = text;
+    }
+}
+
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn respond(stream: TcpStream, metrics: &Mutex<String>) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    // Only the request line matters, e.g. "GET /metrics HTTP/1.1"
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.lock().unwrap().clone(),
+        ),
+        (Some("GET"), _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Renders a snapshot as Prometheus gauges.
+fn exposition(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(out, "# HELP rashboard_{} {}", name, help);
+        let _ = writeln!(out, "# TYPE rashboard_{} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "rashboard_{}{} {}", name, labels, value);
+        }
+    };
+    let single = |value: f64| [(String::new(), value)];
+
+    gauge(
+        "uptime_seconds",
+        "Time since boot.",
+        &single(snapshot.uptime_seconds as f64),
+    );
+    gauge(
+        "memory_used_bytes",
+        "Memory in use.",
+        &single(snapshot.memory.used as f64),
+    );
+    gauge(
+        "memory_total_bytes",
+        "Installed memory.",
+        &single(snapshot.memory.total as f64),
+    );
+    gauge(
+        "memory_available_bytes",
+        "Memory that can be allocated without swapping.",
+        &single(snapshot.memory.available as f64),
+    );
+    gauge(
+        "swap_used_bytes",
+        "Swap in use.",
+        &single(snapshot.memory.swap_used as f64),
+    );
+    gauge(
+        "swap_total_bytes",
+        "Swap space.",
+        &single(snapshot.memory.swap_total as f64),
+    );
+    if let Some(cpu) = &snapshot.cpu {
+        gauge(
+            "cpu_usage_percent",
+            "CPU usage across all cores.",
+            &single(cpu.usage as f64),
+        );
+        let cores: Vec<_> = cpu
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(core, usage)| (labels(&[("core", &core.to_string())]), *usage as f64))
+            .collect();
+        gauge("cpu_core_usage_percent", "CPU usage per core.", &cores);
+        if let Some(steal) = cpu.steal {
+            gauge(
+                "cpu_steal_percent",
+                "CPU time taken by the hypervisor for other guests.",
+                &single(steal as f64),
+            );
+        }
+    }
+
+    if let Some(load) = &snapshot.load_average {
+        let samples = [("1m", load.one), ("5m", load.five), ("15m", load.fifteen)]
+            .map(|(period, value)| (labels(&[("period", period)]), value));
+        gauge("load_average", "System load average.", &samples);
+    }
+
+    let disk_labels = |mount_point: &str, file_system: &str| {
+        labels(&[("mount_point", mount_point), ("file_system", file_system)])
+    };
+    let used: Vec<_> = snapshot
+        .disks
+        .iter()
+        .map(|disk| {
+            let labels = disk_labels(&disk.mount_point, &disk.file_system);
+            (labels, disk.used as f64)
+        })
+        .collect();
+    gauge("disk_used_bytes", "Space in use per filesystem.", &used);
+    let total: Vec<_> = snapshot
+        .disks
+        .iter()
+        .map(|disk| {
+            let labels = disk_labels(&disk.mount_point, &disk.file_system);
+            (labels, disk.total as f64)
+        })
+        .collect();
+    gauge("disk_total_bytes", "Size per filesystem.", &total);
+
+    let inodes = |count: fn(&InodeStats) -> u64| -> Vec<_> {
+        snapshot
+            .disks
+            .iter()
+            .filter_map(|disk| {
+                let labels = disk_labels(&disk.mount_point, &disk.file_system);
+                Some((labels, count(disk.inodes.as_ref()?) as f64))
+            })
+            .collect()
+    };
+    gauge(
+        "disk_inodes_used",
+        "Inodes in use per filesystem.",
+        &inodes(|inodes| inodes.used),
+    );
+    gauge(
+        "disk_inodes_total",
+        "Inodes per filesystem.",
+        &inodes(|inodes| inodes.total),
+    );
+
+    if let Some(updates) = snapshot.updates {
+        gauge(
+            "updates_pending",
+            "Package upgrades available.",
+            &single(updates as f64),
+        );
+    }
+
+    let programs: Vec<_> = snapshot
+        .programs
+        .iter()
+        .filter_map(|program| {
+            let up = matches!(program.state.as_deref()?, "running" | "active");
+            Some((
+                labels(&[("program", &program.name)]),
+                if up { 1.0 } else { 0.0 },
+            ))
+        })
+        .collect();
+    gauge(
+        "program_up",
+        "Whether a watched program or unit is running.",
+        &programs,
+    );
+
+    out
+}
+
+/// Formats `{name="value",...}`, escaping values as the format requires.
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let pairs: Vec<String> = pairs
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use num_format::{Locale, ToFormattedString};
+use serde::{Deserialize, Serialize};
+
+/// Whether byte sizes are shown in powers of 1024 (KiB, MiB, ...) or
+/// powers of 1000 (KB, MB, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteUnits {
+    Binary,
+    Decimal,
+}
+
+impl ByteUnits {
+    fn base(self) -> f64 {
+        match self {
+            ByteUnits::Binary => 1024.0,
+            ByteUnits::Decimal => 1000.0,
+        }
+    }
+
+    fn names(self) -> [&'static str; 5] {
+        match self {
+            ByteUnits::Binary => ["B", "KiB", "MiB", "GiB", "TiB"],
+            ByteUnits::Decimal => ["B", "KB", "MB", "GB", "TB"],
+        }
+    }
+}
+
+/// How numbers are shown: the units for byte sizes, and the locale whose
+/// digit grouping and decimal separator to use, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub byte_units: ByteUnits,
+    /// `None` leaves digits ungrouped, with a `.` before the decimals.
+    pub locale: Option<Locale>,
+}
+
+/// Formats a count, grouping its digits if a locale is set, e.g. "1,048,576".
+pub fn format_count(count: u64, format: NumberFormat) -> String {
+    match format.locale {
+        Some(locale) => count.to_formatted_string(&locale),
+        None => count.to_string(),
+    }
+}
+
+/// Formats `value` with one decimal, grouped like `format_count`.
+fn format_decimal(value: f64, format: NumberFormat) -> String {
+    let Some(locale) = format.locale else {
+        return format!("{:.1}", value);
+    };
+    // Round first, so 9.96 becomes "10.0" rather than "9.10"
+    let tenths = (value * 10.0).round() as u64;
+    format!(
+        "{}{}{}",
+        (tenths / 10).to_formatted_string(&locale),
+        locale.decimal(),
+        tenths % 10
+    )
+}
+
+/// Formats a byte count with the largest unit that keeps the value at or
+/// above 1, e.g. "3.2 GiB".
+pub fn format_bytes(bytes: u64, format: NumberFormat) -> String {
+    let units = format.byte_units;
+    let names = units.names();
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= units.base() && unit < names.len() - 1 {
+        value /= units.base();
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", format_count(bytes, format), names[0])
+    } else {
+        format!("{} {}", format_decimal(value, format), names[unit])
+    }
+}
+
+/// How memory and swap usage are shown, cycled through with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFormat {
+    /// "4.0 GiB / 16.0 GiB (25.0%)"
+    #[default]
+    Both,
+    /// "4.0 GiB / 16.0 GiB"
+    Absolute,
+    /// "25.0%"
+    Percent,
+}
+
+impl UsageFormat {
+    pub fn next(self) -> Self {
+        match self {
+            UsageFormat::Both => UsageFormat::Absolute,
+            UsageFormat::Absolute => UsageFormat::Percent,
+            UsageFormat::Percent => UsageFormat::Both,
+        }
+    }
+
+    /// What is shown, for the footer message after switching.
+    pub fn description(self) -> &'static str {
+        match self {
+            UsageFormat::Both => "amounts and percentages",
+            UsageFormat::Absolute => "amounts",
+            UsageFormat::Percent => "percentages",
+        }
+    }
+}
+
+/// Formats `used` out of `total` bytes as `usage` asks for.
+pub fn format_usage(used: u64, total: u64, format: NumberFormat, usage: UsageFormat) -> String {
+    let percent = if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    };
+    let amounts = || {
+        format!(
+            "{} / {}",
+            format_bytes(used, format),
+            format_bytes(total, format)
+        )
+    };
+    match usage {
+        UsageFormat::Both => format!("{} ({:.1}%)", amounts(), percent),
+        UsageFormat::Absolute => amounts(),
+        UsageFormat::Percent => format!("{:.1}%", percent),
+    }
+}
+
+/// Formats a throughput in bytes per second, e.g. "1.5 MiB/s".
+pub fn format_rate(bytes_per_second: f64, format: NumberFormat) -> String {
+    format!(
+        "{}/s",
+        format_bytes(bytes_per_second.max(0.0) as u64, format)
+    )
+}
+
+/// Formats a clock speed given in MHz, e.g. "800 MHz" or "3.20 GHz".
+pub fn format_frequency(mhz: u64) -> String {
+    if mhz < 1000 {
+        format!("{} MHz", mhz)
+    } else {
+        format!("{:.2} GHz", mhz as f64 / 1000.0)
+    }
+}
+
+/// Formats how long ago something happened in its largest whole unit, e.g.
+/// "4m ago".
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    match seconds {
+        0..60 => format!("{}s ago", seconds),
+        60..3600 => format!("{}m ago", seconds / 60),
+        3600..86400 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+/// Formats a duration in whole seconds as days, hours, minutes and seconds,
+/// e.g. "412d 3h 7m 55s". Days are never rolled up into larger units.
+pub fn format_uptime(seconds: u64) -> String {
+    format!(
+        "{}d {}h {}m {}s",
+        seconds / 86400,
+        (seconds % 86400) / 3600,
+        (seconds % 3600) / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plain numbers in binary units, as the config has them by default.
+    const PLAIN: NumberFormat = NumberFormat {
+        byte_units: ByteUnits::Binary,
+        locale: None,
+    };
+
+    #[test]
+    fn numbers_are_grouped_by_the_configured_locale() {
+        let format = NumberFormat {
+            locale: Some(Locale::de),
+            ..PLAIN
+        };
+
+        assert_eq!(format_count(1_048_576, format), "1.048.576");
+        assert_eq!(format_bytes(1536, format), "1,5 KiB");
+        assert_eq!(format_count(1_048_576, PLAIN), "1048576");
+    }
+
+    #[test]
+    fn bytes_are_shown_in_binary_or_decimal_units() {
+        let decimal = NumberFormat {
+            byte_units: ByteUnits::Decimal,
+            ..PLAIN
+        };
+
+        assert_eq!(format_bytes(999, PLAIN), "999 B");
+        assert_eq!(format_bytes(1536, PLAIN), "1.5 KiB");
+        assert_eq!(format_bytes(1536, decimal), "1.5 KB");
+        assert_eq!(format_bytes(1000, PLAIN), "1000 B");
+        assert_eq!(format_bytes(1000, decimal), "1.0 KB");
+        assert_eq!(format_bytes(4 * 1024 * 1024 * 1024, PLAIN), "4.0 GiB");
+        assert_eq!(format_bytes(4 * 1024 * 1024 * 1024, decimal), "4.3 GB");
+        // Past the largest unit the value just grows
+        assert_eq!(format_bytes(2048 * 1024_u64.pow(4), PLAIN), "2048.0 TiB");
+    }
+
+    #[test]
+    fn uptime_and_age_are_split_into_their_units() {
+        assert_eq!(format_uptime(0), "0d 0h 0m 0s");
+        assert_eq!(format_uptime(90061), "1d 1h 1m 1s");
+        // Days aren't rolled up into years
+        assert_eq!(format_uptime(400 * 86400 + 59), "400d 0h 0m 59s");
+
+        assert_eq!(format_age(Duration::from_millis(900)), "0s ago");
+        assert_eq!(format_age(Duration::from_secs(59)), "59s ago");
+        assert_eq!(format_age(Duration::from_secs(60)), "1m ago");
+        assert_eq!(format_age(Duration::from_secs(7199)), "1h ago");
+        assert_eq!(format_age(Duration::from_secs(3 * 86400)), "3d ago");
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::snapshot::Snapshot;
+
+/// An append-only file of snapshots, one JSON object per line. Once it would
+/// grow past its size cap the file is moved aside to `<path>.1`, replacing
+/// the previous one, and a fresh file is started.
+pub struct MetricsLog {
+    path: PathBuf,
+    max_size: u64,
+    file: File,
+    size: u64,
+}
+
+impl MetricsLog {
+    pub fn open(path: &Path, max_size: u64) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(MetricsLog {
+            path: path.to_path_buf(),
+            max_size,
+            file,
+            size,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let mut line = serde_json::to_string(snapshot).map_err(io::Error::other)?;
+        line.push('\n');
+
+        // A cap of zero means the log is never rotated
+        if self.max_size > 0 && self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        fs::rename(&self.path, rotated)?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
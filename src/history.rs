@@ -0,0 +1,46 @@
+use std::collections::VecDeque;
+
+/// The most recent samples of a value, oldest first, for drawing trends.
+pub struct History {
+    samples: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        History {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds a sample, dropping the oldest once the history is full.
+    pub fn push(&mut self, sample: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Keeps at most `capacity` samples from now on, dropping the oldest.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.samples.len() > capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    /// The most samples kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The newest `count` samples, oldest first.
+    pub fn latest(&self, count: usize) -> Vec<u64> {
+        let skip = self.samples.len().saturating_sub(count);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+}
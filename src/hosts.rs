@@ -0,0 +1,140 @@
+use std::io;
+use std::process::Command;
+
+use crate::config::RemoteHost;
+
+/// What one of the `hosts` reported, or why it couldn't be reached.
+pub struct HostStatus {
+    pub name: String,
+    pub stats: Result<HostStats, String>,
+}
+
+/// A remote host's readings, with sizes in bytes.
+#[derive(Debug, PartialEq)]
+pub struct HostStats {
+    pub uptime: u64,
+    /// One minute load average.
+    pub load: f64,
+    pub cores: usize,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    /// Space on the root filesystem.
+    pub disk_used: u64,
+    pub disk_total: u64,
+}
+
+/// Run on each host, printing what `parse_host_stats` reads, one reading per
+/// line. It reads `/proc` and uses `nproc`, so only Linux hosts can be
+/// watched, and starts with `uname -s` to tell the others apart.
+const HOST_PROBE: &str = "uname -s; cat /proc/uptime /proc/loadavg; nproc; \
+    grep -E '^(MemTotal|MemAvailable):' /proc/meminfo; df -Pk / | tail -n 1";
+
+pub fn remote_host_stats(host: &RemoteHost) -> Result<HostStats, String> {
+    let output = Command::new("ssh")
+        .args(host.ssh_args())
+        .arg(HOST_PROBE)
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "ssh not found".to_string(),
+            _ => format!("Failed to execute ssh: {}", err),
+        })?;
+    if !output.status.success() {
+        // ssh says on its last line what went wrong, e.g. a refused connection
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr
+            .lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .map_or_else(|| format!("ssh failed ({})", output.status), str::to_string));
+    }
+    parse_host_stats(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reads the output of `HOST_PROBE`.
+fn parse_host_stats(output: &str) -> Result<HostStats, String> {
+    let mut lines = output.lines();
+    match lines.next().map(str::trim) {
+        Some("Linux") => {}
+        Some(system) if !system.is_empty() => {
+            return Err(format!(
+                "Unsupported host, it runs {} and only Linux is supported",
+                system
+            ))
+        }
+        _ => return Err("No output from the host".to_string()),
+    }
+    parse_linux_host_stats(lines).ok_or_else(|| "Unexpected output from the host".to_string())
+}
+
+/// Reads the `/proc` and `df` part of `HOST_PROBE`'s output.
+fn parse_linux_host_stats<'a>(mut lines: impl Iterator<Item = &'a str>) -> Option<HostStats> {
+    let mut field = |index: usize| -> Option<String> {
+        lines
+            .next()?
+            .split_whitespace()
+            .nth(index)
+            .map(str::to_string)
+    };
+
+    // 12345.67 23456.78
+    let uptime = field(0)?.parse::<f64>().ok()? as u64;
+    // 0.52 0.58 0.59 1/123 4567
+    let load = field(0)?.parse().ok()?;
+    let cores = field(0)?.parse().ok()?;
+    // MemTotal:  16318412 kB
+    let memory_total: u64 = field(1)?.parse().ok()?;
+    let memory_available: u64 = field(1)?.parse().ok()?;
+    // /dev/sda1  102687672  41194444  56234068  43% /
+    let mut df = lines.next()?.split_whitespace().skip(1);
+    let disk_total: u64 = df.next()?.parse().ok()?;
+    let disk_used: u64 = df.next()?.parse().ok()?;
+
+    Some(HostStats {
+        uptime,
+        load,
+        cores,
+        memory_used: memory_total.saturating_sub(memory_available) * 1024,
+        memory_total: memory_total * 1024,
+        disk_used: disk_used * 1024,
+        disk_total: disk_total * 1024,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_host_readings_are_parsed() {
+        let output = "\
+Linux
+12345.67 23456.78
+0.52 0.58 0.59 1/123 4567
+4
+MemTotal:        1000000 kB
+MemAvailable:     250000 kB
+/dev/sda1  102400  40960  61440  40% /
+";
+        assert_eq!(
+            parse_host_stats(output),
+            Ok(HostStats {
+                uptime: 12345,
+                load: 0.52,
+                cores: 4,
+                memory_used: 750000 * 1024,
+                memory_total: 1000000 * 1024,
+                disk_used: 40960 * 1024,
+                disk_total: 102400 * 1024,
+            })
+        );
+        assert_eq!(
+            parse_host_stats("Linux\n12345.67 23456.78\n"),
+            Err("Unexpected output from the host".to_string())
+        );
+        // Without /proc, BSDs and macOS print little more than their name
+        assert_eq!(
+            parse_host_stats("Darwin\n4\n/dev/disk1s1  102400  40960  61440  40% /\n"),
+            Err("Unsupported host, it runs Darwin and only Linux is supported".to_string())
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Deserializer};
+
+use crate::config::Config;
+
+/// Something a key press can make the dashboard do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Help,
+    TogglePause,
+    RefreshPanel,
+    FocusNext,
+    FocusPrevious,
+    ToggleMaximize,
+    MovePanelUp,
+    MovePanelDown,
+    /// Hide or show the panel at this index into `config.panels`.
+    TogglePanel(usize),
+    SortByCpu,
+    SortByMemory,
+    CycleUsageFormat,
+    KillProcess,
+    FilterProcesses,
+    ShowTaskLog,
+    RestartTask,
+    PauseTask,
+    StartTask,
+    ExportSnapshot,
+    ResetNetworkTotals,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Help => "Show or hide this help",
+            Action::TogglePause => "Pause or resume refreshing",
+            Action::RefreshPanel => "Refresh the focused panel now",
+            Action::FocusNext => "Focus the next panel",
+            Action::FocusPrevious => "Focus the previous panel",
+            Action::ToggleMaximize => "Toggle full screen for the focused panel",
+            Action::MovePanelUp => "Move the focused panel up",
+            Action::MovePanelDown => "Move the focused panel down",
+            Action::TogglePanel(_) => "Show or hide a panel",
+            Action::SortByCpu => "Sort top processes by CPU",
+            Action::SortByMemory => "Sort top processes by memory",
+            Action::CycleUsageFormat => "Show memory and swap as amounts, percentages or both",
+            Action::KillProcess => "Send SIGTERM to the selected process",
+            Action::FilterProcesses => "Filter top processes by name",
+            Action::ShowTaskLog => "Show the log of the selected pueue task",
+            Action::RestartTask => "Restart the selected pueue task",
+            Action::PauseTask => "Pause the selected pueue task",
+            Action::StartTask => "Start or resume the selected pueue task",
+            Action::ExportSnapshot => "Write the current readings to a JSON file",
+            Action::ResetNetworkTotals => "Count the network totals from zero again",
+            Action::ScrollUp => "Scroll the focused panel up",
+            Action::ScrollDown => "Scroll the focused panel down",
+            Action::PageUp => "Scroll the focused panel up a page",
+            Action::PageDown => "Scroll the focused panel down a page",
+        }
+    }
+}
+
+/// A key with its modifiers, as written in the `keys` table of the config,
+/// e.g. "j", "K", "ctrl-d", "shift-up" or "pgdn".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        // A lone "-" is a key, not a modifier separator
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{}` in `{}`", modifier, text)),
+            }
+            rest = key;
+        }
+
+        let mut code = match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pgup" => KeyCode::PageUp,
+            "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            name if name.len() > 1 && name.starts_with('f') => match name[1..].parse() {
+                Ok(number @ 1..=12) => KeyCode::F(number),
+                _ => return Err(format!("unknown key `{}`", rest)),
+            },
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key `{}`", rest)),
+                }
+            }
+        };
+
+        // Terminals report these shifted keys as a key of their own
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            match code {
+                KeyCode::Tab => code = KeyCode::BackTab,
+                KeyCode::Char(c) => code = KeyCode::Char(c.to_ascii_uppercase()),
+                _ => return Ok(Key { code, modifiers }),
+            }
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(Key { code, modifiers })
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// What the `keys` table in the config maps a key to: an action, or "none"
+/// to leave the key unbound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMapping {
+    None,
+    Action(Action),
+}
+
+impl<'de> Deserialize<'de> for KeyMapping {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Not an untagged enum, whose error wouldn't say which actions there are
+        let value = toml::Value::deserialize(deserializer)?;
+        if value.as_str() == Some("none") {
+            return Ok(KeyMapping::None);
+        }
+        Action::deserialize(value)
+            .map(KeyMapping::Action)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A key, with modifiers, bound to an action.
+pub struct Binding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: Action,
+}
+
+impl Binding {
+    fn new(code: KeyCode, action: Action) -> Self {
+        Binding {
+            code,
+            modifiers: KeyModifiers::NONE,
+            action,
+        }
+    }
+
+    fn from_key(key: Key, action: Action) -> Self {
+        Binding {
+            code: key.code,
+            modifiers: key.modifiers,
+            action,
+        }
+    }
+
+    fn ctrl(code: KeyCode, action: Action) -> Self {
+        Binding {
+            code,
+            modifiers: KeyModifiers::CONTROL,
+            action,
+        }
+    }
+
+    fn shift(code: KeyCode, action: Action) -> Self {
+        Binding {
+            code,
+            modifiers: KeyModifiers::SHIFT,
+            action,
+        }
+    }
+
+    /// Whether a key event triggers this binding. Shift is ignored for
+    /// characters, since terminals disagree on reporting it for ones like
+    /// `?`, and the key code already tells shifted keys apart for those and
+    /// for `BackTab`.
+    fn matches(&self, key: &KeyEvent) -> bool {
+        let shift_matters = !matches!(self.code, KeyCode::Char(_) | KeyCode::BackTab);
+        key.code == self.code
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+                == self.modifiers.contains(KeyModifiers::CONTROL)
+            && (!shift_matters
+                || key.modifiers.contains(KeyModifiers::SHIFT)
+                    == self.modifiers.contains(KeyModifiers::SHIFT))
+    }
+
+    /// How the key is written in the help overlay.
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift-Tab".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(number) => format!("F{}", number),
+            code => format!("{:?}", code),
+        };
+
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            format!("Ctrl-{}", key.to_uppercase())
+        } else if self.modifiers.contains(KeyModifiers::SHIFT) {
+            format!("Shift-{}", key)
+        } else {
+            key
+        }
+    }
+}
+
+/// The full keybinding table, which the key handler and the help overlay
+/// both read from. Navigation works both with the arrow keys and vim style,
+/// hjkl, and the `keys` in the config replace what their keys do.
+pub fn bindings(config: &Config) -> Vec<Binding> {
+    let mut bindings = vec![Binding::new(KeyCode::Char(config.quit_key), Action::Quit)];
+    if config.quit_on_ctrl_c {
+        bindings.push(Binding::ctrl(KeyCode::Char('c'), Action::Quit));
+    }
+    if config.quit_on_esc {
+        bindings.push(Binding::new(KeyCode::Esc, Action::Quit));
+    }
+
+    bindings.extend([
+        Binding::new(KeyCode::Char('?'), Action::Help),
+        Binding::new(KeyCode::Char(' '), Action::TogglePause),
+        Binding::new(KeyCode::Char('p'), Action::TogglePause),
+        Binding::new(KeyCode::Char('r'), Action::RefreshPanel),
+        Binding::new(KeyCode::Tab, Action::FocusNext),
+        Binding::new(KeyCode::Right, Action::FocusNext),
+        Binding::new(KeyCode::Char('l'), Action::FocusNext),
+        Binding::new(KeyCode::BackTab, Action::FocusPrevious),
+        Binding::new(KeyCode::Left, Action::FocusPrevious),
+        Binding::new(KeyCode::Char('h'), Action::FocusPrevious),
+        Binding::new(KeyCode::Enter, Action::ToggleMaximize),
+        Binding::shift(KeyCode::Up, Action::MovePanelUp),
+        Binding::shift(KeyCode::Down, Action::MovePanelDown),
+        Binding::new(KeyCode::Char('c'), Action::SortByCpu),
+        Binding::new(KeyCode::Char('m'), Action::SortByMemory),
+        Binding::new(KeyCode::Char('%'), Action::CycleUsageFormat),
+        Binding::new(KeyCode::Char('K'), Action::KillProcess),
+        Binding::new(KeyCode::Char('/'), Action::FilterProcesses),
+        Binding::new(KeyCode::Char('L'), Action::ShowTaskLog),
+        Binding::new(KeyCode::Char('R'), Action::RestartTask),
+        Binding::new(KeyCode::Char('P'), Action::PauseTask),
+        Binding::new(KeyCode::Char('S'), Action::StartTask),
+        Binding::new(KeyCode::Char('e'), Action::ExportSnapshot),
+        Binding::new(KeyCode::Char('z'), Action::ResetNetworkTotals),
+        Binding::new(KeyCode::Up, Action::ScrollUp),
+        Binding::new(KeyCode::Char('k'), Action::ScrollUp),
+        Binding::new(KeyCode::Down, Action::ScrollDown),
+        Binding::new(KeyCode::Char('j'), Action::ScrollDown),
+        Binding::new(KeyCode::PageUp, Action::PageUp),
+        Binding::ctrl(KeyCode::Char('u'), Action::PageUp),
+        Binding::new(KeyCode::PageDown, Action::PageDown),
+        Binding::ctrl(KeyCode::Char('d'), Action::PageDown),
+    ]);
+    for (index, digit) in ('1'..='9').enumerate() {
+        bindings.push(Binding::new(
+            KeyCode::Char(digit),
+            Action::TogglePanel(index),
+        ));
+    }
+
+    // Sorted, so the help overlay lists overrides the same way every time
+    let mut overrides: Vec<_> = config.keys.iter().collect();
+    overrides.sort_by_key(|(key, _)| Binding::from_key(**key, Action::Quit).label());
+    for (key, _) in &overrides {
+        bindings.retain(|binding| binding.code != key.code || binding.modifiers != key.modifiers);
+    }
+    for (key, mapping) in overrides {
+        let KeyMapping::Action(action) = *mapping else {
+            continue;
+        };
+        // Next to the other keys for the action, for the help overlay
+        let position = bindings
+            .iter()
+            .rposition(|binding| binding.action == action)
+            .map_or(bindings.len(), |i| i + 1);
+        bindings.insert(position, Binding::from_key(*key, action));
+    }
+    bindings
+}
+
+/// The action bound to a key press, earlier bindings winning.
+pub fn action_for(bindings: &[Binding], key: &KeyEvent) -> Option<Action> {
+    bindings
+        .iter()
+        .find(|binding| binding.matches(key))
+        .map(|binding| binding.action)
+}
+
+/// How the first key bound to `action` is written, for the hints in panel
+/// titles, or `None` when the action has no key.
+pub fn label_for(bindings: &[Binding], action: Action) -> Option<String> {
+    bindings
+        .iter()
+        .find(|binding| binding.action == action)
+        .map(Binding::label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_from_the_config_replace_the_defaults() {
+        let config: Config = toml::from_str(
+            r#"keys = { x = "kill_process", K = "none", "ctrl-n" = "focus_next", "shift-j" = { toggle_panel = 0 } }"#,
+        )
+        .unwrap();
+        let bindings = bindings(&config);
+        let action = |code: KeyCode, modifiers: KeyModifiers| {
+            action_for(&bindings, &KeyEvent::new(code, modifiers))
+        };
+
+        assert_eq!(
+            action(KeyCode::Char('x'), KeyModifiers::NONE),
+            Some(Action::KillProcess)
+        );
+        assert_eq!(action(KeyCode::Char('K'), KeyModifiers::SHIFT), None);
+        assert_eq!(
+            action(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            Some(Action::FocusNext)
+        );
+        assert_eq!(
+            action(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            Some(Action::TogglePanel(0))
+        );
+        // The defaults for both navigation styles are still there
+        assert_eq!(
+            action(KeyCode::Char('j'), KeyModifiers::NONE),
+            Some(Action::ScrollDown)
+        );
+        assert_eq!(
+            action(KeyCode::Down, KeyModifiers::NONE),
+            Some(Action::ScrollDown)
+        );
+        assert_eq!(
+            action(KeyCode::Char('h'), KeyModifiers::NONE),
+            Some(Action::FocusPrevious)
+        );
+        assert_eq!(
+            action(KeyCode::Left, KeyModifiers::NONE),
+            Some(Action::FocusPrevious)
+        );
+    }
+
+    #[test]
+    fn keys_are_read_with_their_modifiers() {
+        let key = |text: &str| text.parse::<Key>();
+        let ok = |code, modifiers| Ok(Key { code, modifiers });
+
+        assert_eq!(key("ctrl-d"), ok(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert_eq!(
+            key("Ctrl-Shift-Up"),
+            ok(KeyCode::Up, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+        // Shifted characters and tab are keys of their own
+        assert_eq!(key("shift-j"), ok(KeyCode::Char('J'), KeyModifiers::NONE));
+        assert_eq!(key("shift-tab"), ok(KeyCode::BackTab, KeyModifiers::NONE));
+        assert_eq!(key("ctrl--"), ok(KeyCode::Char('-'), KeyModifiers::CONTROL));
+        assert_eq!(key("-"), ok(KeyCode::Char('-'), KeyModifiers::NONE));
+        assert_eq!(
+            key("alt-x"),
+            Err("unknown modifier `alt` in `alt-x`".to_string())
+        );
+
+        // Names of keys are read in any case, characters keep theirs
+        assert_eq!(key("PgDn"), ok(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(key("F12"), ok(KeyCode::F(12), KeyModifiers::NONE));
+        assert_eq!(key("K"), ok(KeyCode::Char('K'), KeyModifiers::NONE));
+        assert_eq!(key("k"), ok(KeyCode::Char('k'), KeyModifiers::NONE));
+        assert_eq!(key("f13"), Err("unknown key `f13`".to_string()));
+
+        // "none" unbinds a key, it isn't one
+        assert_eq!(key("none"), Err("unknown key `none`".to_string()));
+        let config: Config = toml::from_str(r#"keys = { q = "none" }"#).unwrap();
+        assert_eq!(
+            config.keys.values().collect::<Vec<_>>(),
+            [&KeyMapping::None]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::ops::Range;
+
+use tui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Smallest panel that still has room for a line of content.
+pub const MIN_PANEL_HEIGHT: u16 = 3;
+pub const MIN_PANEL_WIDTH: u16 = 20;
+
+/// Works out where each panel goes, as `(index into the panels, area)`,
+/// given each panel's minimum height. With a number of `columns` the panels
+/// fill a grid of that width row by row. Otherwise they are stacked while
+/// they all fit, then split into two columns. Either way, on screens too
+/// small for that only a window of panels around the focused one is shown,
+/// in a single column. Returns `None` if not even a single panel fits.
+pub fn layout_panels(
+    area: Rect,
+    min_heights: &[u16],
+    focused: usize,
+    columns: Option<u16>,
+) -> Option<Vec<(usize, Rect)>> {
+    if area.height < MIN_PANEL_HEIGHT || area.width < MIN_PANEL_WIDTH {
+        return None;
+    }
+    let count = min_heights.len();
+    if count == 0 {
+        return Some(Vec::new());
+    }
+    let height = |range: Range<usize>| min_heights[range].iter().sum::<u16>();
+
+    if let Some(columns) = columns {
+        // As many columns as asked for, but no narrower than a panel can be
+        let columns = usize::from(columns.min(area.width / MIN_PANEL_WIDTH).max(1)).min(count);
+        let rows: Vec<u16> = min_heights
+            .chunks(columns)
+            .map(|row| row.iter().copied().max().unwrap_or(0))
+            .collect();
+        if rows.iter().sum::<u16>() <= area.height {
+            return Some(grid(area, columns, &rows, count));
+        }
+    } else {
+        if height(0..count) <= area.height {
+            return Some(stack(area, 0..count, min_heights));
+        }
+
+        let rows = count.div_ceil(2);
+        let fits = height(0..rows) <= area.height && height(rows..count) <= area.height;
+        if fits && area.width >= 2 * MIN_PANEL_WIDTH {
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+                .split(area);
+
+            let mut areas = stack(columns[0], 0..rows, min_heights);
+            areas.extend(stack(columns[1], rows..count, min_heights));
+            return Some(areas);
+        }
+    }
+
+    // Keep the focused panel in view, scrolling the rest off screen: the
+    // panels before it come first, those after it fill what is left
+    let focused = focused.min(count - 1);
+    let (mut first, mut last) = (focused, focused + 1);
+    while first > 0 && height(first - 1..last) <= area.height {
+        first -= 1;
+    }
+    while last < count && height(first..last + 1) <= area.height {
+        last += 1;
+    }
+    Some(stack(area, first..last, min_heights))
+}
+
+/// Stacks the panels in `range` from top to bottom of `area`, sharing out
+/// what is left over their minimum heights evenly.
+fn stack(area: Rect, range: Range<usize>, min_heights: &[u16]) -> Vec<(usize, Rect)> {
+    let heights = share(area.height, &min_heights[range.clone()]);
+    let mut y = area.y;
+    range
+        .zip(heights)
+        .map(|(i, height)| {
+            let rect = Rect::new(area.x, y, area.width, height);
+            y += height;
+            (i, rect)
+        })
+        .collect()
+}
+
+/// Lays `count` panels out left to right in rows of `columns`, with the
+/// rows at least as high as given.
+fn grid(area: Rect, columns: usize, rows: &[u16], count: usize) -> Vec<(usize, Rect)> {
+    let constraints = vec![Constraint::Ratio(1, columns as u32); columns];
+    let mut y = area.y;
+    let mut areas = Vec::with_capacity(count);
+    for (row, height) in share(area.height, rows).into_iter().enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints.clone())
+            .split(Rect::new(area.x, y, area.width, height));
+        let first = row * columns;
+        areas.extend((first..count.min(first + columns)).zip(cells));
+        y += height;
+    }
+    areas
+}
+
+/// Splits `total` into one part per minimum, each at least its minimum where
+/// there is room, with the rest spread evenly and the first parts getting
+/// any remainder.
+fn share(total: u16, mins: &[u16]) -> Vec<u16> {
+    let needed: u16 = mins.iter().sum();
+    if needed > total || mins.is_empty() {
+        // Only ever a single panel on a short screen, let it have the lot
+        let mut parts = vec![0; mins.len()];
+        if let Some(part) = parts.first_mut() {
+            *part = total;
+        }
+        return parts;
+    }
+
+    let spare = total - needed;
+    let count = mins.len() as u16;
+    mins.iter()
+        .enumerate()
+        .map(|(i, min)| min + spare / count + u16::from((i as u16) < spare % count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_fills_rows_left_to_right() {
+        let areas = layout_panels(Rect::new(0, 0, 90, 20), &[4, 3, 3, 3], 0, Some(3)).unwrap();
+        let positions: Vec<(u16, u16)> = areas.iter().map(|(_, area)| (area.x, area.y)).collect();
+
+        assert_eq!(positions, [(0, 0), (30, 0), (60, 0), (0, 11)]);
+        // Too narrow for three columns of panels, so only one is left
+        let areas = layout_panels(Rect::new(0, 0, 30, 20), &[4, 3, 3, 3], 0, Some(3)).unwrap();
+        assert!(areas.iter().all(|(_, area)| area.x == 0));
+        assert_eq!(areas.len(), 4);
+    }
+}
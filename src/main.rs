@@ -1,23 +1,360 @@
-use std::ffi::OsStr;
-use std::io;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::net::IpAddr;
+use std::panic;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
+mod alerts;
+mod background;
+mod battery;
+mod config;
+mod errors;
+mod exporter;
+mod format;
+mod headless;
+mod history;
+mod hosts;
+mod keys;
+mod layout;
+mod metrics;
+mod notifications;
+mod snapshot;
+mod state;
+mod theme;
+mod updates;
+mod webhook;
+
+use alerts::{AlertKind, AlertLog, Level};
+use background::Background;
+use battery::Battery;
+use clap::Parser;
+use config::{
+    CommandPanel, Config, ConfigWatcher, CpuCoreView, LogSource, PanelAction, PanelKind,
+    ProgramCheck,
+};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+        MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    tty::IsTty,
+};
+use errors::ErrorLog;
+use exporter::MetricsServer;
+use format::{
+    format_age, format_bytes, format_count, format_frequency, format_rate, format_uptime,
+    format_usage, NumberFormat, UsageFormat,
+};
+use headless::MetricsLog;
+use history::History;
+use hosts::{remote_host_stats, HostStatus};
+use keys::{Action, Binding};
+use layout::{layout_panels, MIN_PANEL_HEIGHT, MIN_PANEL_WIDTH};
+use metrics::{
+    CpuStats, DiskRate, DiskStats, InterfaceRate, LoadStats, MemoryStats, MetricsSource,
+    ProcessSort, ProcessStates, ProcessStats, ProgramStats, SystemInfo, SystemSource,
+    TemperatureStats, Termination,
 };
-use sysinfo::System;
+use notifications::Notifier;
+use regex::{Regex, RegexBuilder};
+use snapshot::{ProgramSnapshot, Snapshot};
+use state::UiState;
+use sysinfo::{Pid, System};
+use theme::{Theme, ThemeError, ThemeName};
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::{bar, Marker},
+    text::{Span, Spans},
+    widgets::{
+        Axis, BarChart, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType, Paragraph,
+        Sparkline, Wrap,
+    },
     Terminal,
 };
+use updates::{PackageManager, PackageUpdate};
+use webhook::WebhookSender;
+
+/// Sparkline levels for `ascii`, coarser than the Unicode eighths.
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: "_",
+    one_eighth: "_",
+    empty: " ",
+};
+
+/// Width of the per-core usage bars in the CPU panel.
+const CPU_BAR_WIDTH: usize = 20;
+
+/// Sensors that don't report a critical temperature are colored against this.
+const DEFAULT_CRITICAL_TEMPERATURE: f32 = 90.0;
+
+/// How long a first quit key press waits for the confirming second press.
+const QUIT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a transient message stays in the footer.
+const STATUS_MESSAGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a panel's command has to be running before its title gets a
+/// spinner, so commands that finish right away don't make it flicker.
+const SPINNER_DELAY: Duration = Duration::from_millis(500);
+/// How long each spinner frame is shown.
+const SPINNER_FRAME: Duration = Duration::from_millis(100);
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const ASCII_SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+
+/// Longest `--once` waits for systemd and the package manager to answer
+/// before printing the summary without their part.
+const ONCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest the main loop waits for input before checking for a shutdown
+/// signal, since the wait itself isn't interrupted by one.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Parser)]
+#[command(version, about = "A small terminal dashboard for system status")]
+struct Args {
+    /// Refresh interval, in milliseconds or with a unit suffix (e.g. 500, 500ms, 2s, 1m)
+    /// [default: 1000, overrides `tick_rate` in the config]
+    #[arg(
+        long,
+        visible_alias = "interval",
+        value_name = "DURATION",
+        value_parser = config::parse_duration
+    )]
+    tick_rate: Option<Duration>,
+
+    /// Pueue group to show, may be repeated (overrides `pueue_groups` in the config)
+    #[arg(long = "pueue-group", value_name = "GROUP")]
+    pueue_groups: Vec<String>,
+
+    /// Draw graphs with ASCII characters instead of Unicode blocks (overrides
+    /// `ascii` in the config)
+    #[arg(long)]
+    ascii: bool,
+
+    /// Arrange the panels in a grid this many columns wide (overrides
+    /// `columns` in the config)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    columns: Option<u16>,
+
+    /// Profile from the config to use, instead of `default_profile`
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Color theme (overrides `theme` in the config)
+    #[arg(long, value_enum)]
+    theme: Option<ThemeName>,
+
+    /// TOML file mapping color roles to color names or #rrggbb, on top of the theme
+    #[arg(long, value_name = "FILE")]
+    theme_file: Option<PathBuf>,
+
+    /// Mark readings OK, ! or !! by their thresholds, not only by color
+    /// (overrides `severity_markers` in the config)
+    #[arg(long)]
+    severity_markers: bool,
+
+    /// Don't show the dashboard, append the readings to a log file as JSON
+    /// lines every tick instead
+    #[arg(long)]
+    headless: bool,
+
+    /// File for --headless to log to (overrides `log_file` in the config)
+    #[arg(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Print a summary of the last readings to the terminal on quitting
+    #[arg(long)]
+    summary: bool,
+
+    /// Print a summary of the readings once, without showing the dashboard,
+    /// and exit
+    #[arg(long, conflicts_with_all = ["headless", "summary", "metrics_port"])]
+    once: bool,
+
+    /// Start right away, instead of first sampling the CPU twice so usage is
+    /// known from the very first frame
+    #[arg(long)]
+    no_warm_up: bool,
+
+    /// Also serve the readings for Prometheus at http://ADDRESS:PORT/metrics
+    #[arg(long, value_name = "PORT")]
+    metrics_port: Option<u16>,
+
+    /// Address for --metrics-port to listen on, 0.0.0.0 for every interface
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        default_value = "127.0.0.1",
+        requires = "metrics_port"
+    )]
+    metrics_address: IpAddr,
+
+    /// Write the default config, with every setting explained, to
+    /// ~/.config/rashboard/config.toml and exit
+    #[arg(long)]
+    init_config: bool,
+
+    /// Let --init-config replace an existing config
+    #[arg(long, requires = "init_config")]
+    force: bool,
+}
+
+impl Args {
+    /// The theme named in `config`, with the theme file's colors and the
+    /// configured borders and gradient applied.
+    fn load_theme(&self, config: &Config) -> Result<Theme, ThemeError> {
+        let theme = Theme {
+            borders: config.border_style,
+            // Green to red is what the color blind theme is there to avoid
+            gradient: config.gauge_gradient.enabled() && config.theme != ThemeName::ColorBlind,
+            ..config.theme.theme()
+        };
+        match &self.theme_file {
+            Some(path) => Theme::from_file(path, theme),
+            None => Ok(theme),
+        }
+    }
+
+    /// Applies the options that override the config file.
+    fn apply(&self, config: &mut Config) {
+        if let Some(tick_rate) = self.tick_rate {
+            config.tick_rate = tick_rate;
+        }
+        if !self.pueue_groups.is_empty() {
+            config.pueue_groups = self.pueue_groups.clone();
+        }
+        if let Some(columns) = self.columns {
+            config.columns = Some(columns);
+        }
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if self.ascii {
+            config.ascii = true;
+        }
+        if self.severity_markers {
+            config.severity_markers = true;
+        }
+        if let Some(log_file) = &self.log_file {
+            config.log_file = Some(log_file.clone());
+        }
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Parse arguments before touching the terminal, so errors stay readable
+    let args = Args::parse();
+    // Before loading the config, since a broken one is a reason to start over
+    if args.init_config {
+        match config::init(args.force) {
+            Ok(path) => {
+                println!("Wrote {}", path.display());
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("rashboard: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+    let mut config = match Config::load(args.profile.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("rashboard: {}", err);
+            std::process::exit(1);
+        }
+    };
+    args.apply(&mut config);
+    let theme = match args.load_theme(&config) {
+        Ok(theme) => theme,
+        Err(err) => {
+            eprintln!("rashboard: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let metrics_server = match args
+        .metrics_port
+        .map(|port| MetricsServer::start((args.metrics_address, port).into()))
+        .transpose()
+    {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("rashboard: failed to serve metrics: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // CPU usage takes two samples, both taken now so that even the first
+    // frame or logged reading has it
+    let mut source = SystemSource::new(config.history_length);
+    if !args.no_warm_up {
+        source.warm_up();
+    }
+
+    if args.once {
+        let mut app = App::new(config, theme, args, Box::new(source));
+        print!("{}", app.once_summary(ONCE_TIMEOUT));
+        return Ok(());
+    }
+
+    if args.headless {
+        let Some(path) = config.log_path() else {
+            eprintln!("rashboard: no log file configured and no home directory to put one in");
+            std::process::exit(1);
+        };
+        let log = match MetricsLog::open(&path, config.max_log_size) {
+            Ok(log) => log,
+            Err(err) => {
+                eprintln!("rashboard: failed to open {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        };
+        let shutdown = shutdown_flag()?;
+        let mut app = App::new(config, theme, args, Box::new(source));
+        app.metrics_server = metrics_server;
+        return run_headless(app, log, &shutdown);
+    }
+
+    // Raw mode and the alternate screen make a mess of a pipe or a log file
+    if !io::stdout().is_tty() {
+        eprintln!("rashboard: stdout is not a terminal; use --headless to run without one");
+        std::process::exit(1);
+    }
+
+    // Put the terminal back before the panic message is printed, otherwise
+    // it ends up garbled on the alternate screen and raw mode stays on.
+    // Background threads leave it alone, the UI is still running then.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            let _ = restore_terminal();
+        }
+        default_hook(info);
+    }));
+
+    // SIGTERM and friends only raise a flag, so the loop can wind down and
+    // the terminal gets restored instead of being left in raw mode
+    let shutdown = shutdown_flag()?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -25,185 +362,5800 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let tick_rate = Duration::from_millis(1000);
-    let mut last_tick = Instant::now();
+    let summary = args.summary;
+    let mut app = App::new(config, theme, args, Box::new(source));
+    app.metrics_server = metrics_server;
+    let state_path = UiState::default_path();
+    if app.config.remember_ui_state {
+        if let Some(state) = state_path.as_deref().and_then(UiState::load) {
+            app.restore_ui_state(state);
+        }
+    }
+    let result = run(&mut terminal, &mut app, &shutdown);
+
+    // Restore terminal, even if the loop bailed out with an error
+    restore_terminal()?;
 
-    let mut sys = System::new_all();
+    if let Some(path) = state_path.filter(|_| app.config.remember_ui_state) {
+        if let Err(err) = app.ui_state().save(&path) {
+            eprintln!("rashboard: failed to save {}: {}", path.display(), err);
+        }
+    }
+
+    // Only now, or it would go to the alternate screen and vanish with it
+    if summary && result.is_ok() {
+        print!("{}", app.snapshot().summary(app.config.number_format()));
+    }
+    result
+}
+
+fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    shutdown: &AtomicBool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut last_tick = Instant::now();
+    app.refresh();
 
-    loop {
-        terminal.draw(|f| ui(f, &mut sys))?;
+    while !shutdown.load(Ordering::Relaxed) {
+        app.poll_background();
+        terminal.draw(|f| ui(f, app))?;
 
+        let tick_rate = app.config.tick_rate;
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+            .unwrap_or_else(|| Duration::from_secs(0))
+            .min(SHUTDOWN_CHECK_INTERVAL);
 
         if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') {
-                    // Exit on 'q'
-                    break;
+            match event::read()? {
+                Event::Key(key) if app.handle_key(key) => return Ok(()),
+                Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => {
+                    app.handle_click(mouse.column, mouse.row);
+                }
+                // Redrawn at the top of the loop right away, onto a cleared
+                // screen so none of the old layout is left behind. Until then
+                // clicks can't land on where panels used to be.
+                Event::Resize(width, height) => {
+                    terminal.resize(Rect::new(0, 0, width, height))?;
+                    app.panel_areas.clear();
                 }
+                _ => {}
             }
         }
 
         if last_tick.elapsed() >= tick_rate {
+            app.reload_config_if_changed();
+            app.refresh();
             last_tick = Instant::now();
         }
     }
+    Ok(())
+}
+
+/// A flag raised by SIGTERM, SIGINT or SIGHUP, for the main loop to stop on.
+fn shutdown_flag() -> io::Result<Arc<AtomicBool>> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [
+        signal_hook::consts::SIGTERM,
+        signal_hook::consts::SIGINT,
+        signal_hook::consts::SIGHUP,
+    ] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))?;
+    }
+    Ok(shutdown)
+}
+
+/// Collects the same readings as the dashboard, but logs a snapshot each
+/// tick rather than drawing anything.
+fn run_headless(
+    mut app: App,
+    mut log: MetricsLog,
+    shutdown: &AtomicBool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut last_tick = Instant::now();
+    app.refresh();
+
+    while !shutdown.load(Ordering::Relaxed) {
+        app.poll_background();
+
+        if last_tick.elapsed() >= app.config.tick_rate {
+            app.reload_config_if_changed();
+            app.refresh();
+            last_tick = Instant::now();
+            if let Err(err) = log.append(&app.snapshot()) {
+                eprintln!(
+                    "rashboard: failed to write {}: {}",
+                    log.path().display(),
+                    err
+                );
+                std::process::exit(1);
+            }
+        }
+
+        let timeout = app
+            .config
+            .tick_rate
+            .saturating_sub(last_tick.elapsed())
+            .min(SHUTDOWN_CHECK_INTERVAL);
+        thread::sleep(timeout);
+    }
+    Ok(())
+}
 
-    // Restore terminal
+/// Leaves raw mode and the alternate screen and shows the cursor again.
+fn restore_terminal() -> io::Result<()> {
     disable_raw_mode()?;
     execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+        DisableMouseCapture,
+        cursor::Show
+    )
+}
 
-    Ok(())
+/// State carried across ticks of the main loop.
+struct App {
+    config: Config,
+    /// Where memory, disk, CPU, process, network and sensor readings come from.
+    source: Box<dyn MetricsSource>,
+    batteries: Vec<Battery>,
+    /// Memory usage in percent, one sample per refresh.
+    memory_history: History,
+    /// Memory that isn't available, and swap in use, in percent, one sample
+    /// per memory refresh.
+    pressure_history: (History, History),
+    /// Received and transmitted bytes per second over all shown interfaces.
+    network_history: (History, History),
+    /// When the network panel's session totals started counting.
+    network_totals_since: chrono::DateTime<chrono::Local>,
+    package_manager: Option<PackageManager>,
+    updates: Background<Result<Vec<PackageUpdate>, String>>,
+    updates_scroll: Scroll,
+    pueue: Background<Vec<PueueGroup>>,
+    /// `systemctl is-active` states, one per program, when checking systemd units.
+    services: Background<Result<Vec<String>, String>>,
+    sessions: Background<Result<Vec<Session>, String>>,
+    containers: Background<Result<Vec<Container>, String>>,
+    gpus: Background<Result<Vec<Gpu>, String>>,
+    pings: Background<Result<Vec<Ping>, String>>,
+    /// One reading per configured host, in order.
+    hosts: Background<Vec<HostStatus>>,
+    sockets: Background<Result<Vec<Socket>, String>>,
+    /// The last `log_lines` lines of the `log_source`, oldest first.
+    logs: Background<Result<Vec<String>, String>>,
+    logs_scroll: Scroll,
+    /// When each panel last fetched its data.
+    last_refresh: HashMap<PanelKind, Instant>,
+    /// One per entry in `config.command_panels`, in the same order.
+    commands: Vec<CommandOutput>,
+    /// Non-fatal failures of background jobs and config reloads.
+    errors: ErrorLog,
+    alerts: AlertLog,
+    alerts_scroll: Scroll,
+    webhook: WebhookSender,
+    /// When the quit key was first pressed, while waiting for confirmation.
+    quit_requested: Option<Instant>,
+    process_sort: ProcessSort,
+    /// How the memory panel shows memory and swap usage.
+    usage_format: UsageFormat,
+    /// Row of the top processes panel that is highlighted.
+    selected_process: usize,
+    /// The process to send SIGTERM to, while waiting for confirmation.
+    kill_requested: Option<KillRequest>,
+    /// Narrows the top processes panel down by name, while set.
+    process_filter: Option<ProcessFilter>,
+    /// A pueue command waiting for confirmation, like `kill_requested`.
+    task_action_requested: Option<TaskActionRequest>,
+    /// A confirmed pueue command on its way, with the footer message for
+    /// when it's done.
+    task_action: Option<Background<String>>,
+    /// A destructive command panel action waiting for confirmation.
+    panel_action_requested: Option<PanelAction>,
+    /// The overlay showing a command panel action's output, while it is open.
+    panel_action_output: Option<ActionOutput>,
+    /// Index into `config.panels` of the panel that has focus.
+    focused: usize,
+    /// Index into `config.panels` of the panel shown full screen, if any.
+    /// It follows focus, so moving focus flips through the panels.
+    maximized: Option<usize>,
+    /// Where each panel was drawn last, as `(index into config.panels, area)`,
+    /// for working out which panel a click landed on.
+    panel_areas: Vec<(usize, Rect)>,
+    /// Indices into `config.panels` of the panels toggled off.
+    hidden: HashSet<usize>,
+    pueue_scroll: Scroll,
+    /// Index of the highlighted task among all tasks in the pueue panel.
+    selected_task: usize,
+    /// The overlay showing one pueue task's log, while it is open.
+    task_log: Option<TaskLog>,
+    bindings: Vec<Binding>,
+    show_help: bool,
+    /// While paused nothing is refreshed, so the values on screen hold still.
+    paused: bool,
+    hostname: String,
+    system_info: SystemInfo,
+    theme: Theme,
+    /// Transient message for the footer, with when it was posted.
+    status_message: Option<(String, Instant)>,
+    /// Kept to re-apply command line overrides when the config is reloaded.
+    args: Args,
+    config_watcher: Option<ConfigWatcher>,
+    /// Serves each refresh's readings, with `--metrics-port`.
+    metrics_server: Option<MetricsServer>,
+    notifier: Notifier,
 }
 
-fn ui<B: Backend>(f: &mut tui::Frame<B>, sys: &mut System) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(
-            [
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
+/// The cached output of a command panel.
+struct CommandOutput {
+    output: Background<Result<String, String>>,
+    scroll: Scroll,
+}
 
-    // Memory and Disk Usage
-    draw_memory_disk(f, chunks[0], sys);
+impl CommandOutput {
+    fn new(panel: &CommandPanel) -> Self {
+        let panel = panel.clone();
+        CommandOutput {
+            output: Background::new(panel.interval, move || {
+                command_output(&panel.command, &panel.args)
+            }),
+            scroll: Scroll::default(),
+        }
+    }
+}
 
-    // Uptime
-    draw_uptime(f, chunks[1]);
+/// Vertical scroll position of a panel whose content can overflow it.
+#[derive(Default)]
+struct Scroll {
+    offset: u16,
+    /// Sizes seen on the last render, used to clamp the offset.
+    content_height: u16,
+    viewport_height: u16,
+}
 
-    // Available Updates via apt
-    draw_apt_updates(f, chunks[2]);
+impl Scroll {
+    fn max_offset(&self) -> u16 {
+        self.content_height.saturating_sub(self.viewport_height)
+    }
 
-    // Status of Certain Programs
-    draw_program_status(f, chunks[3], sys, &["nginx", "mysql"]);
+    fn apply(&mut self, action: Action) {
+        let page = self.viewport_height.max(1) as i32;
+        let delta = match action {
+            Action::ScrollUp => -1,
+            Action::ScrollDown => 1,
+            Action::PageUp => -page,
+            Action::PageDown => page,
+            _ => return,
+        };
+        self.offset = (self.offset as i32 + delta).clamp(0, self.max_offset() as i32) as u16;
+    }
 
-    // Pueue Tasks Status
-    draw_pueue_status(f, chunks[4]);
-}
+    /// Scrolls just far enough for `line` to be in a viewport this high.
+    fn reveal(&mut self, line: u16, viewport_height: u16) {
+        if line < self.offset {
+            self.offset = line;
+        } else if line >= self.offset + viewport_height {
+            self.offset = line + 1 - viewport_height;
+        }
+    }
 
-fn draw_memory_disk<B: Backend>(f: &mut tui::Frame<B>, area: Rect, sys: &mut System) {
-    // sys.refresh_memory();
-    sys.refresh_all();
+    /// Like `update`, but an offset at the bottom stays there as content
+    /// grows, so the newest lines of a log stay in view.
+    fn update_following(&mut self, content_height: u16, viewport_height: u16) -> u16 {
+        if self.offset >= self.max_offset() {
+            self.offset = u16::MAX;
+        }
+        self.update(content_height, viewport_height)
+    }
 
-    let total_memory = sys.total_memory() / 1024; // in MB
-    let used_memory = sys.used_memory() / 1024; // in MB
-    let memory_usage = format!("Memory Usage: {}/{} MB", used_memory, total_memory);
+    /// Records the current sizes and returns the offset to render with.
+    /// Shrinking content pulls the offset back so nothing is scrolled past.
+    fn update(&mut self, content_height: u16, viewport_height: u16) -> u16 {
+        self.content_height = content_height;
+        self.viewport_height = viewport_height;
+        self.offset = self.offset.min(self.max_offset());
+        self.offset
+    }
+}
 
-    let text = format!("{}", memory_usage);
+/// The log of one pueue task, fetched in the background and kept as lines so
+/// only the part on screen has to be rendered.
+struct TaskLog {
+    id: usize,
+    output: Background<Result<Vec<String>, String>>,
+    scroll: Scroll,
+}
 
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().title("Memory Usage").borders(Borders::ALL))
-        .style(Style::default().fg(Color::Cyan));
+impl TaskLog {
+    /// Starts fetching the log of task `id`, again every `interval` so a
+    /// running task's output keeps coming in.
+    fn new(id: usize, interval: Duration) -> Self {
+        TaskLog {
+            id,
+            output: Background::new(interval, move || pueue_log(id)),
+            scroll: Scroll::default(),
+        }
+    }
+}
 
-    f.render_widget(paragraph, area);
+/// What a command panel action printed, run once in the background.
+struct ActionOutput {
+    command_line: String,
+    output: Background<Result<String, String>>,
+    scroll: Scroll,
 }
 
-fn draw_uptime<B: Backend>(f: &mut tui::Frame<B>, area: Rect) {
-    // No need to refresh the system for uptime
-    let uptime_seconds = sysinfo::System::uptime();
+impl ActionOutput {
+    fn new(action: &PanelAction) -> Self {
+        let command = action.command.clone();
+        let args = action.args.clone();
+        ActionOutput {
+            command_line: action.command_line(),
+            // An interval that never runs out, so it is only run the once
+            output: Background::new(Duration::MAX, move || action_output(&command, &args)),
+            scroll: Scroll::default(),
+        }
+    }
+}
 
-    let uptime = format!(
-        "Uptime: {}d {}h {}m {}s",
-        uptime_seconds / 86400,
-        (uptime_seconds % 86400) / 3600,
-        (uptime_seconds % 3600) / 60,
-        uptime_seconds % 60
-    );
+/// Something the pueue panel can tell pueue to do with a task.
+#[derive(Clone, Copy)]
+enum TaskAction {
+    Restart,
+    Pause,
+    Start,
+}
 
-    let paragraph = Paragraph::new(uptime)
-        .block(
-            Block::default()
-                .title("System Uptime")
-                .borders(Borders::ALL),
-        )
-        .style(Style::default().fg(Color::Green));
+impl TaskAction {
+    fn command(self) -> &'static str {
+        match self {
+            TaskAction::Restart => "restart",
+            TaskAction::Pause => "pause",
+            TaskAction::Start => "start",
+        }
+    }
 
-    f.render_widget(paragraph, area);
+    fn verb(self) -> &'static str {
+        match self {
+            TaskAction::Restart => "Restart",
+            TaskAction::Pause => "Pause",
+            TaskAction::Start => "Start",
+        }
+    }
 }
 
-fn draw_apt_updates<B: Backend>(f: &mut tui::Frame<B>, area: Rect) {
-    let output = Command::new("bash")
-        .arg("-c")
-        .arg("apt list --upgradable 2>/dev/null | wc -l")
-        .output()
-        .expect("Failed to execute command");
+/// A pueue task picked to act on, captured when asked like `KillRequest`.
+struct TaskActionRequest {
+    action: TaskAction,
+    id: usize,
+}
 
-    let count_str = String::from_utf8_lossy(&output.stdout);
-    let count: i32 = count_str.trim().parse().unwrap_or(1) - 1; // Exclude header line
+/// A process picked to be killed. The PID and name are captured when asked,
+/// so a re-sort while the prompt is up can't change what gets killed.
+struct KillRequest {
+    pid: Pid,
+    name: String,
+}
+
+/// What was typed after `/` in the top processes panel. It is matched as a
+/// case-insensitive regex, or as plain text while it isn't a valid one, such
+/// as halfway through typing a group.
+struct ProcessFilter {
+    input: String,
+    /// Whether keys still go to the input line, until Enter.
+    editing: bool,
+    pattern: Regex,
+}
 
-    let updates = format!("Available Updates: {}", count);
+impl ProcessFilter {
+    fn new() -> Self {
+        ProcessFilter {
+            input: String::new(),
+            editing: true,
+            pattern: Self::compile(""),
+        }
+    }
 
-    let paragraph = Paragraph::new(updates)
-        .block(Block::default().title("Apt Updates").borders(Borders::ALL))
-        .style(Style::default().fg(Color::Yellow));
+    fn set_input(&mut self, input: String) {
+        self.pattern = Self::compile(&input);
+        self.input = input;
+    }
 
-    f.render_widget(paragraph, area);
+    fn compile(input: &str) -> Regex {
+        RegexBuilder::new(input)
+            .case_insensitive(true)
+            .build()
+            .unwrap_or_else(|_| {
+                RegexBuilder::new(&regex::escape(input))
+                    .case_insensitive(true)
+                    .build()
+                    .expect("escaped text is a valid regex")
+            })
+    }
 }
 
-fn draw_program_status<B: Backend>(
-    f: &mut tui::Frame<B>,
-    area: Rect,
-    sys: &mut System,
-    programs: &[&str],
-) {
-    sys.refresh_processes(sysinfo::ProcessesToUpdate::All, false);
-    let mut statuses = String::new();
+impl App {
+    fn new(config: Config, theme: Theme, args: Args, source: Box<dyn MetricsSource>) -> Self {
+        let updates_interval = config.intervals.updates.unwrap_or(config.tick_rate);
+        let pueue_interval = config.intervals.pueue.unwrap_or(config.tick_rate);
+        let package_manager = PackageManager::detect();
+        let pueue_groups = config.pueue_groups.clone();
+        let services = services_background(&config);
+        let containers = containers_background(&config);
+        let sessions_interval = config.intervals.users.unwrap_or(config.tick_rate);
+        let gpus_interval = config.intervals.gpu.unwrap_or(config.tick_rate);
+        let bindings = keys::bindings(&config);
+        let commands = config
+            .command_panels
+            .iter()
+            .map(CommandOutput::new)
+            .collect();
+
+        App {
+            source,
+            batteries: battery::read_batteries(),
+            memory_history: History::new(config.history_length),
+            pressure_history: (
+                History::new(config.history_length),
+                History::new(config.history_length),
+            ),
+            network_history: (
+                History::new(config.history_length),
+                History::new(config.history_length),
+            ),
+            network_totals_since: chrono::Local::now(),
+            package_manager,
+            updates: Background::new(updates_interval, move || match package_manager {
+                Some(manager) => manager.list_updates(),
+                None => Err("no supported package manager".to_string()),
+            }),
+            updates_scroll: Scroll::default(),
+            pueue: Background::new(pueue_interval, move || pueue_status(&pueue_groups)),
+            services,
+            sessions: Background::new(sessions_interval, logged_in_sessions),
+            containers,
+            gpus: Background::new(gpus_interval, gpu_stats),
+            pings: pings_background(&config),
+            hosts: hosts_background(&config),
+            sockets: sockets_background(&config),
+            logs: logs_background(&config),
+            logs_scroll: Scroll::default(),
+            last_refresh: HashMap::new(),
+            commands,
+            errors: ErrorLog::default(),
+            alerts: AlertLog::default(),
+            alerts_scroll: Scroll::default(),
+            webhook: WebhookSender::new(),
+            quit_requested: None,
+            process_sort: ProcessSort::Cpu,
+            usage_format: UsageFormat::default(),
+            selected_process: 0,
+            kill_requested: None,
+            process_filter: None,
+            task_action_requested: None,
+            task_action: None,
+            panel_action_requested: None,
+            panel_action_output: None,
+            focused: 0,
+            maximized: None,
+            panel_areas: Vec::new(),
+            hidden: HashSet::new(),
+            pueue_scroll: Scroll::default(),
+            selected_task: 0,
+            task_log: None,
+            bindings,
+            show_help: false,
+            paused: false,
+            theme,
+            hostname: System::host_name().unwrap_or_else(|| "unknown host".to_string()),
+            system_info: metrics::collect_system_info(),
+            status_message: None,
+            args,
+            config_watcher: ConfigWatcher::new(),
+            metrics_server: None,
+            notifier: Notifier::new(),
+            // Last, the fields above are built from it
+            config,
+        }
+    }
+
+    /// Swaps in the config file's new contents if it changed. A config that
+    /// fails to load is reported and the current one is kept.
+    fn reload_config_if_changed(&mut self) {
+        if !self
+            .config_watcher
+            .as_ref()
+            .is_some_and(ConfigWatcher::changed)
+        {
+            return;
+        }
+
+        let mut config = match Config::load(self.args.profile.as_deref()) {
+            Ok(config) => config,
+            Err(err) => {
+                self.errors.record("config", &err.summary());
+                self.set_status(format!("Config not reloaded: {}", err.summary()));
+                return;
+            }
+        };
+        self.args.apply(&mut config);
+        let theme = match self.args.load_theme(&config) {
+            Ok(theme) => theme,
+            Err(err) => {
+                let message = err.to_string();
+                let summary = message.lines().next().unwrap_or_default();
+                self.errors.record("theme", summary);
+                self.set_status(format!("Config not reloaded: {}", summary));
+                return;
+            }
+        };
 
-    for &program in programs {
-        let is_running = sys
-            .processes_by_exact_name(OsStr::new(program))
-            .next()
-            .is_some();
-        let status = if is_running {
-            format!("{}: Running\n", program)
+        self.updates
+            .set_interval(config.intervals.updates.unwrap_or(config.tick_rate));
+        let pueue_interval = config.intervals.pueue.unwrap_or(config.tick_rate);
+        if config.pueue_groups == self.config.pueue_groups {
+            self.pueue.set_interval(pueue_interval);
+        } else {
+            let pueue_groups = config.pueue_groups.clone();
+            self.pueue = Background::new(pueue_interval, move || pueue_status(&pueue_groups));
+            self.pueue_scroll = Scroll::default();
+        }
+        if config.programs == self.config.programs {
+            self.services
+                .set_interval(config.intervals.programs.unwrap_or(config.tick_rate));
         } else {
-            format!("{}: Not Running\n", program)
+            self.services = services_background(&config);
+        }
+        self.sessions
+            .set_interval(config.intervals.users.unwrap_or(config.tick_rate));
+        self.gpus
+            .set_interval(config.intervals.gpu.unwrap_or(config.tick_rate));
+        if config.log_source == self.config.log_source && config.log_lines == self.config.log_lines
+        {
+            self.logs
+                .set_interval(config.intervals.logs.unwrap_or(config.tick_rate));
+        } else {
+            self.logs = logs_background(&config);
+        }
+        if config.ports == self.config.ports {
+            self.sockets
+                .set_interval(config.intervals.ports.unwrap_or(config.tick_rate));
+        } else {
+            self.sockets = sockets_background(&config);
+        }
+        if config.ping_hosts == self.config.ping_hosts {
+            self.pings
+                .set_interval(config.intervals.ping.unwrap_or(config.tick_rate));
+        } else {
+            self.pings = pings_background(&config);
+        }
+        if config.hosts == self.config.hosts {
+            self.hosts
+                .set_interval(config.intervals.hosts.unwrap_or(config.tick_rate));
+        } else {
+            self.hosts = hosts_background(&config);
+        }
+        if config.docker_filter == self.config.docker_filter {
+            self.containers
+                .set_interval(config.intervals.docker.unwrap_or(config.tick_rate));
+        } else {
+            self.containers = containers_background(&config);
+        }
+
+        if config.command_panels != self.config.command_panels {
+            self.commands = config
+                .command_panels
+                .iter()
+                .map(CommandOutput::new)
+                .collect();
+        }
+
+        self.source.set_history_length(config.history_length);
+        self.memory_history.set_capacity(config.history_length);
+        self.pressure_history.0.set_capacity(config.history_length);
+        self.pressure_history.1.set_capacity(config.history_length);
+        self.network_history.0.set_capacity(config.history_length);
+        self.network_history.1.set_capacity(config.history_length);
+        self.bindings = keys::bindings(&config);
+        self.theme = theme;
+        if config.panels != self.config.panels {
+            self.hidden.clear();
+        }
+        self.focused = self.focused.min(config.panels.len().saturating_sub(1));
+        self.maximized = self.maximized.map(|_| self.focused);
+        self.config = config;
+        self.set_status("Config reloaded");
+    }
+
+    /// Handles a key press, returning whether the app should exit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let action = keys::action_for(&self.bindings, &key);
+
+        // The help overlay swallows everything but the keys that close it
+        if self.show_help {
+            if matches!(action, Some(Action::Help | Action::Quit)) || key.code == KeyCode::Esc {
+                self.show_help = false;
+            }
+            return false;
+        }
+
+        // The task log overlay scrolls, and otherwise only closes
+        if let Some(log) = &mut self.task_log {
+            match action {
+                Some(
+                    action @ (Action::ScrollUp
+                    | Action::ScrollDown
+                    | Action::PageUp
+                    | Action::PageDown),
+                ) => log.scroll.apply(action),
+                Some(Action::ShowTaskLog | Action::Quit) => self.task_log = None,
+                _ if key.code == KeyCode::Esc => self.task_log = None,
+                _ => {}
+            }
+            return false;
+        }
+        // And so does the output of a command panel action
+        if let Some(output) = &mut self.panel_action_output {
+            match action {
+                Some(
+                    action @ (Action::ScrollUp
+                    | Action::ScrollDown
+                    | Action::PageUp
+                    | Action::PageDown),
+                ) => output.scroll.apply(action),
+                Some(Action::Quit) => self.panel_action_output = None,
+                _ if key.code == KeyCode::Esc => self.panel_action_output = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        // Only `y` goes ahead with a kill, any other key backs out of it
+        if let Some(request) = self.kill_requested.take() {
+            if key.code == KeyCode::Char('y') {
+                self.kill(request);
+            } else {
+                self.set_status("Kill cancelled");
+            }
+            return false;
+        }
+        if let Some(request) = self.task_action_requested.take() {
+            if key.code == KeyCode::Char('y') {
+                self.run_task_action(request);
+            } else {
+                self.set_status(format!("{} cancelled", request.action.verb()));
+            }
+            return false;
+        }
+        if let Some(action) = self.panel_action_requested.take() {
+            if key.code == KeyCode::Char('y') {
+                self.panel_action_output = Some(ActionOutput::new(&action));
+            } else {
+                self.set_status(format!("{} cancelled", action.command_line()));
+            }
+            return false;
+        }
+
+        // The filter input line takes every key until Enter or Esc
+        if let Some(filter) = self.process_filter.as_mut().filter(|filter| filter.editing) {
+            match key.code {
+                KeyCode::Enter => filter.editing = false,
+                KeyCode::Esc => self.process_filter = None,
+                KeyCode::Backspace => {
+                    let mut input = filter.input.clone();
+                    input.pop();
+                    filter.set_input(input);
+                }
+                KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    filter.set_input(format!("{}{}", filter.input, c));
+                }
+                _ => return false,
+            }
+            self.selected_process = 0;
+            return false;
+        }
+
+        // Esc first leaves full screen, whatever else it is bound to
+        if self.maximized.is_some() && key.code == KeyCode::Esc {
+            self.maximized = None;
+            return false;
+        }
+        // and then drops a process filter that was kept with Enter
+        if self.process_filter.is_some()
+            && key.code == KeyCode::Esc
+            && self.config.panels.get(self.focused) == Some(&PanelKind::Processes)
+        {
+            self.process_filter = None;
+            self.selected_process = 0;
+            return false;
+        }
+
+        // The focused command panel's own keys win over the global ones
+        if let Some(panel_action) = self.focused_panel_action(&key) {
+            self.quit_requested = None;
+            if panel_action.destructive {
+                self.panel_action_requested = Some(panel_action);
+            } else {
+                self.panel_action_output = Some(ActionOutput::new(&panel_action));
+            }
+            return false;
+        }
+
+        if action == Some(Action::Quit) {
+            if !self.config.confirm_quit || self.is_confirming_quit() {
+                return true;
+            }
+            self.quit_requested = Some(Instant::now());
+            return false;
+        }
+        self.quit_requested = None;
+
+        match action {
+            Some(Action::Help) => self.show_help = true,
+            Some(Action::TogglePause) => {
+                self.paused = !self.paused;
+                // Catch up right away instead of showing stale data for a tick
+                if !self.paused {
+                    self.refresh();
+                }
+            }
+            Some(Action::RefreshPanel) => self.refresh_focused(),
+            Some(Action::FocusNext) => self.move_focus(1),
+            Some(Action::FocusPrevious) => self.move_focus(-1),
+            Some(Action::ExportSnapshot) => {
+                let message = match self.snapshot().write() {
+                    Ok(path) => format!("Wrote {}", path.display()),
+                    Err(err) => format!("Failed to write snapshot: {}", err),
+                };
+                self.set_status(message);
+            }
+            Some(Action::ResetNetworkTotals) => {
+                self.source.reset_network_totals();
+                self.network_totals_since = chrono::Local::now();
+                self.set_status("Network totals reset");
+            }
+            Some(Action::TogglePanel(index)) => self.toggle_panel(index),
+            Some(Action::MovePanelUp) => self.move_panel(-1),
+            Some(Action::MovePanelDown) => self.move_panel(1),
+            Some(Action::ToggleMaximize) => {
+                self.maximized = match self.maximized {
+                    Some(_) => None,
+                    None => Some(self.focused),
+                };
+            }
+            Some(Action::SortByCpu) => {
+                self.process_sort = ProcessSort::Cpu;
+                self.set_status("Sorting processes by CPU");
+            }
+            Some(Action::SortByMemory) => {
+                self.process_sort = ProcessSort::Memory;
+                self.set_status("Sorting processes by memory");
+            }
+            Some(Action::CycleUsageFormat) => {
+                self.usage_format = self.usage_format.next();
+                self.set_status(format!(
+                    "Showing memory and swap as {}",
+                    self.usage_format.description()
+                ));
+            }
+            Some(Action::KillProcess) => {
+                if self.config.panels.get(self.focused) != Some(&PanelKind::Processes) {
+                    self.set_status("Focus the top processes panel to kill a process");
+                } else if let Some(process) = self.top_processes().get(self.selected_process) {
+                    self.kill_requested = Some(KillRequest {
+                        pid: process.pid,
+                        name: process.name.clone(),
+                    });
+                }
+            }
+            Some(Action::FilterProcesses) => {
+                if self.config.panels.get(self.focused) != Some(&PanelKind::Processes) {
+                    self.set_status("Focus the top processes panel to filter it");
+                } else {
+                    self.process_filter = Some(ProcessFilter::new());
+                    self.selected_process = 0;
+                }
+            }
+            Some(Action::RestartTask) => self.request_task_action(TaskAction::Restart),
+            Some(Action::PauseTask) => self.request_task_action(TaskAction::Pause),
+            Some(Action::StartTask) => self.request_task_action(TaskAction::Start),
+            Some(Action::ShowTaskLog) => {
+                if self.config.panels.get(self.focused) != Some(&PanelKind::Pueue) {
+                    self.set_status("Focus the pueue panel to view a task's log");
+                } else if let Some(&id) = self.pueue_tasks().get(self.selected_task) {
+                    let interval = self.config.intervals.pueue.unwrap_or(self.config.tick_rate);
+                    self.task_log = Some(TaskLog::new(id, interval));
+                } else {
+                    self.set_status("No pueue task to show the log of");
+                }
+            }
+            Some(
+                action
+                @ (Action::ScrollUp | Action::ScrollDown | Action::PageUp | Action::PageDown),
+            ) if self.config.panels.get(self.focused) == Some(&PanelKind::Processes) => {
+                self.move_process_selection(action);
+            }
+            Some(
+                action
+                @ (Action::ScrollUp | Action::ScrollDown | Action::PageUp | Action::PageDown),
+            ) if self.config.panels.get(self.focused) == Some(&PanelKind::Pueue)
+                && !self.pueue_tasks().is_empty() =>
+            {
+                self.move_task_selection(action);
+            }
+            Some(action) => {
+                if let Some(scroll) = self.focused_scroll() {
+                    scroll.apply(action);
+                }
+            }
+            None => {}
+        }
+        false
+    }
+
+    /// Focuses the panel under a mouse click, if there is one.
+    fn handle_click(&mut self, column: u16, row: u16) {
+        if self.show_help {
+            return;
+        }
+        if let Some(&(i, _)) = self.panel_areas.iter().find(|(_, area)| {
+            (area.left()..area.right()).contains(&column)
+                && (area.top()..area.bottom()).contains(&row)
+        }) {
+            self.focused = i;
+        }
+    }
+
+    /// The processes listed by the top processes panel, in display order.
+    fn top_processes(&self) -> Vec<ProcessStats> {
+        let filter = self.process_filter.as_ref().map(|filter| &filter.pattern);
+        self.source
+            .top_processes(self.process_sort, self.config.top_processes, filter)
+    }
+
+    /// Moves the highlight in the top processes panel, stopping at either end.
+    fn move_process_selection(&mut self, action: Action) {
+        let count = self.top_processes().len();
+        let page = self.config.top_processes.max(1) as isize;
+        let delta = match action {
+            Action::ScrollUp => -1,
+            Action::ScrollDown => 1,
+            Action::PageUp => -page,
+            Action::PageDown => page,
+            _ => return,
         };
-        statuses.push_str(&status);
+        let last = count.saturating_sub(1) as isize;
+        self.selected_process = (self.selected_process as isize + delta).clamp(0, last) as usize;
     }
 
-    let paragraph = Paragraph::new(statuses)
-        .block(
-            Block::default()
-                .title("Program Status")
-                .borders(Borders::ALL),
-        )
-        .style(Style::default().fg(Color::Magenta));
+    /// IDs of the tasks listed in the pueue panel, in display order.
+    fn pueue_tasks(&self) -> Vec<usize> {
+        self.pueue
+            .latest()
+            .into_iter()
+            .flatten()
+            .filter_map(|group| group.status.as_ref().ok())
+            .flat_map(|status| status.lines().filter_map(pueue_task_id))
+            .collect()
+    }
 
-    f.render_widget(paragraph, area);
-}
+    /// The action the focused command panel binds to `key`, if any.
+    fn focused_panel_action(&self, key: &KeyEvent) -> Option<PanelAction> {
+        let Some(PanelKind::Command(title)) = self.config.panels.get(self.focused) else {
+            return None;
+        };
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return None;
+        }
+        let panel = &self.config.command_panels[self.config.command_panel(title)?];
+        panel
+            .actions
+            .iter()
+            .find(|action| key.code == KeyCode::Char(action.key))
+            .cloned()
+    }
 
-fn draw_pueue_status<B: Backend>(f: &mut tui::Frame<B>, area: Rect) {
-    let output = Command::new("pueue")
-        .arg("status")
-        .arg("-g")
-        .arg("SERVICES")
-        .output()
-        .expect("Failed to execute pueue");
+    /// Asks to confirm `action` on the highlighted pueue task.
+    fn request_task_action(&mut self, action: TaskAction) {
+        if self.config.panels.get(self.focused) != Some(&PanelKind::Pueue) {
+            self.set_status("Focus the pueue panel to act on a task");
+        } else if let Some(&id) = self.pueue_tasks().get(self.selected_task) {
+            self.task_action_requested = Some(TaskActionRequest { action, id });
+        } else {
+            self.set_status("No pueue task selected");
+        }
+    }
 
-    let status_str = String::from_utf8_lossy(&output.stdout);
+    /// Starts a confirmed pueue command in the background. How it went is
+    /// reported in the footer once it's done, see `poll_background`.
+    fn run_task_action(&mut self, request: TaskActionRequest) {
+        self.set_status(format!(
+            "Asking pueue to {} task {}…",
+            request.action.command(),
+            request.id
+        ));
+        // An interval that never runs out, so it is only run the once
+        self.task_action = Some(Background::new(Duration::MAX, move || {
+            task_action(&request)
+        }));
+    }
 
-    let paragraph = Paragraph::new(status_str)
-        .block(
-            Block::default()
-                .title("Pueue SERVICES Group")
-                .borders(Borders::ALL),
-        )
-        .style(Style::default().fg(Color::Blue));
+    /// Moves the highlight in the pueue panel, stopping at either end.
+    fn move_task_selection(&mut self, action: Action) {
+        let count = self.pueue_tasks().len();
+        let page = self.pueue_scroll.viewport_height.max(1) as isize;
+        let delta = match action {
+            Action::ScrollUp => -1,
+            Action::ScrollDown => 1,
+            Action::PageUp => -page,
+            Action::PageDown => page,
+            _ => return,
+        };
+        let last = count.saturating_sub(1) as isize;
+        self.selected_task = (self.selected_task as isize + delta).clamp(0, last) as usize;
+    }
 
-    f.render_widget(paragraph, area);
+    /// Sends SIGTERM to a process and reports how it went in the footer.
+    fn kill(&mut self, request: KillRequest) {
+        let target = format!("{} ({})", request.pid, request.name);
+        let message = match self.source.terminate(request.pid) {
+            Termination::Exited => format!("{} has already exited", target),
+            Termination::Sent => format!("Sent SIGTERM to {}", target),
+            Termination::Failed => format!("Failed to send SIGTERM to {}", target),
+            Termination::Unsupported => "SIGTERM is not supported on this platform".to_string(),
+        };
+        self.set_status(message);
+    }
+
+    /// The scroll state of the focused panel, if it scrolls.
+    fn focused_scroll(&mut self) -> Option<&mut Scroll> {
+        match self.config.panels.get(self.focused)? {
+            PanelKind::Updates if self.config.list_updates => Some(&mut self.updates_scroll),
+            PanelKind::Pueue => Some(&mut self.pueue_scroll),
+            PanelKind::Logs => Some(&mut self.logs_scroll),
+            PanelKind::Alerts => Some(&mut self.alerts_scroll),
+            PanelKind::Command(title) => {
+                let index = self.config.command_panel(title)?;
+                Some(&mut self.commands[index].scroll)
+            }
+            _ => None,
+        }
+    }
+
+    /// Indices into `config.panels` of the panels currently on screen.
+    fn visible_panels(&self) -> Vec<usize> {
+        let hide_battery = self.config.hide_missing_battery && self.batteries.is_empty();
+        (0..self.config.panels.len())
+            .filter(|i| !self.hidden.contains(i))
+            .filter(|&i| !(hide_battery && self.config.panels[i] == PanelKind::Battery))
+            .collect()
+    }
+
+    /// Moves focus by `offset` visible panels, wrapping around at either end.
+    fn move_focus(&mut self, offset: isize) {
+        let visible = self.visible_panels();
+        if visible.is_empty() {
+            return;
+        }
+
+        let current = visible.iter().position(|&i| i == self.focused).unwrap_or(0);
+        let next = (current as isize + offset).rem_euclid(visible.len() as isize);
+        self.focused = visible[next as usize];
+        if self.maximized.is_some() {
+            self.maximized = Some(self.focused);
+        }
+    }
+
+    /// The readings as last refreshed, whichever panels are shown.
+    fn snapshot(&self) -> Snapshot {
+        let programs = match self.config.program_check {
+            ProgramCheck::Process => self
+                .source
+                .programs(&self.config.programs)
+                .into_iter()
+                .map(|program| ProgramSnapshot {
+                    state: Some(
+                        if program.processes > 0 {
+                            "running"
+                        } else {
+                            "not running"
+                        }
+                        .to_string(),
+                    ),
+                    name: program.name,
+                })
+                .collect(),
+            ProgramCheck::Systemd => {
+                let states = match self.services.latest() {
+                    Some(Ok(states)) => states.as_slice(),
+                    _ => &[],
+                };
+                self.config
+                    .programs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, unit)| ProgramSnapshot {
+                        name: unit.clone(),
+                        state: states.get(i).cloned(),
+                    })
+                    .collect()
+            }
+        };
+
+        Snapshot {
+            taken_at: chrono::Local::now(),
+            hostname: self.system_info.hostname.clone(),
+            uptime_seconds: self.source.uptime(),
+            memory: self.source.memory(),
+            cpu: self.source.cpu(),
+            load_average: self.source.load(),
+            disks: self.disks(),
+            updates: self
+                .updates
+                .latest()
+                .and_then(|updates| Some(updates.as_ref().ok()?.len())),
+            programs,
+        }
+    }
+
+    /// Takes one round of readings and summarizes them, for `--once`. The
+    /// systemd states and package updates come from background jobs, which
+    /// are waited for, up to `timeout`.
+    fn once_summary(&mut self, timeout: Duration) -> String {
+        self.refresh();
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.poll_background();
+            let services_done = self.config.program_check != ProgramCheck::Systemd
+                || self.services.latest().is_some();
+            let updates_done = self.package_manager.is_none() || self.updates.latest().is_some();
+            if (services_done && updates_done) || Instant::now() >= deadline {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        self.snapshot().summary(self.config.number_format())
+    }
+
+    /// The disks to list, as the config filters them.
+    fn disks(&self) -> Vec<DiskStats> {
+        self.source
+            .disks(self.config.show_pseudo_filesystems, &|mount_point| {
+                self.config.shows_mount(mount_point)
+            })
+    }
+
+    /// Logs memory, swap and disk usage crossing their thresholds, and
+    /// posts those alerts to the webhook.
+    fn check_alerts(&mut self) {
+        let thresholds = &self.config.thresholds;
+        let memory = self.source.memory();
+        let mut readings = vec![(
+            AlertKind::Memory,
+            "Memory".to_string(),
+            percent(memory.used, memory.total),
+            thresholds.memory_warn,
+            thresholds.memory_critical,
+        )];
+        if memory.swap_total > 0 {
+            readings.push((
+                AlertKind::Swap,
+                "Swap".to_string(),
+                percent(memory.swap_used, memory.swap_total),
+                thresholds.swap_warn,
+                thresholds.swap_critical,
+            ));
+        }
+        for disk in self.disks() {
+            readings.push((
+                AlertKind::Disk,
+                format!("Disk {}", disk.mount_point),
+                percent(disk.used, disk.total),
+                thresholds.disk_warn,
+                thresholds.disk_critical,
+            ));
+        }
+
+        for (kind, name, usage, warn, critical) in readings {
+            let Some(alert) = self.alerts.check(kind, &name, usage, warn, critical) else {
+                continue;
+            };
+            if let Some(webhook) = &self.config.webhook {
+                let host = self.system_info.hostname.as_deref();
+                self.webhook.send(webhook, alert, host);
+            }
+        }
+    }
+
+    /// Checks memory and disk usage against their critical thresholds.
+    fn notify_critical(&mut self) {
+        let thresholds = &self.config.thresholds;
+        let memory = self.source.memory();
+        self.notifier.check(
+            "Memory",
+            percent(memory.used, memory.total),
+            thresholds.memory_critical,
+        );
+        for disk in self.disks() {
+            self.notifier.check(
+                &format!("Disk {}", disk.mount_point),
+                percent(disk.used, disk.total),
+                thresholds.disk_critical,
+            );
+        }
+    }
+
+    /// Readings at or past their warning level, worst first, each with what
+    /// is wrong, for the health panel. Programs only count while their panel
+    /// is shown.
+    fn health(&self) -> Vec<(Level, String)> {
+        let thresholds = &self.config.thresholds;
+        let level = usage_level;
+
+        let mut problems = Vec::new();
+        let memory = self.source.memory();
+        let usage = percent(memory.used, memory.total);
+        problems.push((
+            level(usage, thresholds.memory_warn, thresholds.memory_critical),
+            format!("memory at {:.0}%", usage),
+        ));
+        if memory.swap_total > 0 {
+            let usage = percent(memory.swap_used, memory.swap_total);
+            problems.push((
+                level(usage, thresholds.swap_warn, thresholds.swap_critical),
+                format!("swap at {:.0}%", usage),
+            ));
+        }
+        for disk in self.disks() {
+            let usage = percent(disk.used, disk.total);
+            problems.push((
+                level(usage, thresholds.disk_warn, thresholds.disk_critical),
+                format!("disk {} at {:.0}%", disk.mount_point, usage),
+            ));
+        }
+        // Against the core count, like the load average panel
+        if let Some(load) = self.source.load() {
+            let cores = self.source.core_count().max(1);
+            problems.push((
+                level(load.one, cores as f64, cores as f64 * 2.0),
+                format!("load {:.2} on {} cores", load.one, cores),
+            ));
+        }
+
+        if self.config.panels.contains(&PanelKind::Programs) {
+            match self.config.program_check {
+                ProgramCheck::Process => {
+                    for program in self.source.programs(&self.config.programs) {
+                        if program.processes == 0 {
+                            problems
+                                .push((Level::Critical, format!("{} not running", program.name)));
+                        }
+                    }
+                }
+                ProgramCheck::Systemd => {
+                    if let Some(Ok(states)) = self.services.latest() {
+                        for (unit, state) in self.config.programs.iter().zip(states) {
+                            if state != "active" {
+                                problems.push((Level::Critical, format!("{} is {}", unit, state)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        problems.retain(|(level, _)| *level != Level::Normal);
+        // Stable, so equally bad readings stay in the order above
+        problems.sort_by_key(|(level, _)| std::cmp::Reverse(*level));
+        problems
+    }
+
+    /// Hides a shown panel or shows a hidden one, leaving its room to the rest.
+    fn toggle_panel(&mut self, index: usize) {
+        let Some(panel) = self.config.panels.get(index) else {
+            return;
+        };
+        let message = if self.hidden.remove(&index) {
+            format!("Showing {}", panel.label())
+        } else {
+            self.hidden.insert(index);
+            format!("Hid {}, {} shows it again", panel.label(), index + 1)
+        };
+        self.set_status(message);
+    }
+
+    /// Swaps the focused panel with the visible panel `offset` away from it,
+    /// keeping focus on the moved panel. The new order lasts until the
+    /// config is reloaded.
+    fn move_panel(&mut self, offset: isize) {
+        let visible = self.visible_panels();
+        let Some(current) = visible.iter().position(|&i| i == self.focused) else {
+            return;
+        };
+        let Some(&other) = current
+            .checked_add_signed(offset)
+            .and_then(|position| visible.get(position))
+        else {
+            return;
+        };
+
+        self.config.panels.swap(self.focused, other);
+        self.focused = other;
+        if self.maximized.is_some() {
+            self.maximized = Some(other);
+        }
+    }
+
+    /// What was changed on the dashboard, to be put back next time.
+    fn ui_state(&self) -> UiState {
+        let panels = &self.config.panels;
+        UiState {
+            panels: panels.clone(),
+            hidden: self.hidden.iter().map(|&i| panels[i].clone()).collect(),
+            focused: panels.get(self.focused).cloned(),
+            process_sort: self.process_sort,
+            usage_format: self.usage_format,
+        }
+    }
+
+    /// Puts back what was changed on the dashboard last time. The panel
+    /// order only comes back while the same panels are configured, and
+    /// panels since taken out of the config are skipped.
+    fn restore_ui_state(&mut self, state: UiState) {
+        let count = |panels: &[PanelKind], panel: &PanelKind| {
+            panels.iter().filter(|other| *other == panel).count()
+        };
+        let same_panels = state.panels.len() == self.config.panels.len()
+            && state
+                .panels
+                .iter()
+                .all(|panel| count(&state.panels, panel) == count(&self.config.panels, panel));
+        if same_panels {
+            self.config.panels = state.panels;
+        }
+
+        let panels = &self.config.panels;
+        self.hidden = (0..panels.len())
+            .filter(|&i| state.hidden.contains(&panels[i]))
+            .collect();
+        if let Some(focused) = state
+            .focused
+            .and_then(|focused| panels.iter().position(|panel| *panel == focused))
+        {
+            self.focused = focused;
+        }
+        self.process_sort = state.process_sort;
+        self.usage_format = state.usage_format;
+    }
+
+    fn is_confirming_quit(&self) -> bool {
+        self.quit_requested
+            .is_some_and(|requested| requested.elapsed() < QUIT_CONFIRM_TIMEOUT)
+    }
+
+    /// Shows a message in the footer for a few seconds.
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status_message = Some((message.into(), Instant::now()));
+    }
+
+    /// The footer message, if one was posted recently.
+    fn status(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, posted)| posted.elapsed() < STATUS_MESSAGE_TIMEOUT)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Refreshes the system data read by the configured panels. Called once
+    /// per tick, so each subsystem is refreshed at most once however many
+    /// panels read it, and less often for panels with a longer interval.
+    fn refresh(&mut self) {
+        if self.paused {
+            return;
+        }
+
+        // Evaluated up front, since each check also restarts the panel's timer
+        let memory = self.is_due(PanelKind::Memory);
+        let disk_io = self.is_due(PanelKind::DiskIo);
+        let cpu = self.is_due(PanelKind::Cpu);
+        let programs = self.is_due(PanelKind::Programs);
+        let processes = self.is_due(PanelKind::Processes);
+        let process_states = self.is_due(PanelKind::ProcessStates);
+        let network = self.is_due(PanelKind::Network);
+        let temperatures = self.is_due(PanelKind::Temperatures);
+        let batteries = self.is_due(PanelKind::Battery);
+
+        if memory {
+            self.source.refresh_memory();
+            let memory = self.source.memory();
+            let usage = percent(memory.used, memory.total);
+            self.memory_history.push(usage.round() as u64);
+            let unavailable = memory.total.saturating_sub(memory.available);
+            self.pressure_history
+                .0
+                .push(percent(unavailable, memory.total).round() as u64);
+            self.pressure_history
+                .1
+                .push(percent(memory.swap_used, memory.swap_total).round() as u64);
+        }
+        if memory || disk_io {
+            self.source.refresh_disks();
+        }
+        if memory {
+            self.check_alerts();
+        }
+        if memory && self.config.notifications {
+            self.notify_critical();
+        }
+        if cpu {
+            self.source.refresh_cpu();
+        }
+        let checks_processes = self.config.program_check == ProgramCheck::Process;
+        if (programs && checks_processes) || processes || process_states {
+            self.source.refresh_processes();
+        }
+        if network {
+            self.source.refresh_network();
+            let (mut received, mut transmitted) = (0.0, 0.0);
+            for rate in self.source.network_rates() {
+                if !(self.config.exclude_loopback && is_loopback(&rate.name)) {
+                    received += rate.received;
+                    transmitted += rate.transmitted;
+                }
+            }
+            self.network_history.0.push(received as u64);
+            self.network_history.1.push(transmitted as u64);
+        }
+        if temperatures {
+            self.source.refresh_temperatures();
+        }
+        if batteries {
+            self.batteries = battery::read_batteries();
+        }
+
+        if let Some(server) = &self.metrics_server {
+            server.update(&self.snapshot());
+        }
+    }
+
+    /// Whether a panel's data is wanted at all: it is shown, or everything
+    /// is, for the log when running headless, the summary printed by
+    /// `--once` or for the metrics server.
+    fn collects(&self, panel: &PanelKind) -> bool {
+        self.args.headless
+            || self.args.once
+            || self.metrics_server.is_some()
+            || self.config.panels.contains(panel)
+            // Alerts, notifications, memory pressure and health come from the
+            // memory and disk readings
+            || (*panel == PanelKind::Memory
+                && (self.config.panels.contains(&PanelKind::Alerts)
+                    || self.config.panels.contains(&PanelKind::MemoryPressure)
+                    || self.config.panels.contains(&PanelKind::Health)
+                    || self.config.webhook.is_some()
+                    || self.config.notifications))
+    }
+
+    /// Whether a collected panel's data should be fetched this tick,
+    /// recording the fetch if so.
+    fn is_due(&mut self, panel: PanelKind) -> bool {
+        if !self.collects(&panel) {
+            return false;
+        }
+
+        let now = Instant::now();
+        let due = match (
+            self.config.intervals.get(&panel),
+            self.last_refresh.get(&panel),
+        ) {
+            (Some(interval), Some(last)) => now.duration_since(*last) >= interval,
+            _ => true,
+        };
+        if due {
+            self.last_refresh.insert(panel, now);
+        }
+        due
+    }
+
+    /// Fetches the focused panel's data now, rather than once its interval
+    /// is up, and starts the interval over.
+    fn refresh_focused(&mut self) {
+        if self.paused {
+            self.set_status("Paused, press p to resume refreshing");
+            return;
+        }
+        let Some(panel) = self.config.panels.get(self.focused).cloned() else {
+            return;
+        };
+
+        // Panels fed by a command get a new run on the next poll, the
+        // rest are read from the system right away
+        match &panel {
+            PanelKind::Updates => self.updates.rerun(),
+            PanelKind::Pueue => self.pueue.rerun(),
+            PanelKind::Programs if self.config.program_check == ProgramCheck::Systemd => {
+                self.services.rerun()
+            }
+            PanelKind::Users => self.sessions.rerun(),
+            PanelKind::Docker => self.containers.rerun(),
+            PanelKind::Gpu => self.gpus.rerun(),
+            PanelKind::Ping => self.pings.rerun(),
+            PanelKind::Hosts => self.hosts.rerun(),
+            PanelKind::Ports => self.sockets.rerun(),
+            PanelKind::Logs => self.logs.rerun(),
+            PanelKind::Command(title) => {
+                if let Some(index) = self.config.command_panel(title) {
+                    self.commands[index].output.rerun();
+                }
+            }
+            _ => {
+                self.last_refresh.remove(&panel);
+                if panel == PanelKind::MemoryPressure {
+                    self.last_refresh.remove(&PanelKind::Memory);
+                }
+                self.refresh();
+                self.set_status(format!("Refreshed {}", panel.label()));
+                return;
+            }
+        }
+        self.set_status(format!("Refreshing {}…", panel.label()));
+    }
+
+    /// How long the command behind a panel has been running, for panels fed
+    /// by one that is running.
+    fn busy_for(&self, panel: &PanelKind) -> Option<Duration> {
+        match panel {
+            PanelKind::Updates => self.updates.running_for(),
+            PanelKind::Pueue => self.pueue.running_for(),
+            PanelKind::Programs if self.config.program_check == ProgramCheck::Systemd => {
+                self.services.running_for()
+            }
+            PanelKind::Users => self.sessions.running_for(),
+            PanelKind::Docker => self.containers.running_for(),
+            PanelKind::Gpu => self.gpus.running_for(),
+            PanelKind::Ping => self.pings.running_for(),
+            PanelKind::Hosts => self.hosts.running_for(),
+            PanelKind::Ports => self.sockets.running_for(),
+            PanelKind::Logs => self.logs.running_for(),
+            PanelKind::Command(title) => {
+                let index = self.config.command_panel(title)?;
+                self.commands[index].output.running_for()
+            }
+            _ => None,
+        }
+    }
+
+    /// Collects finished external command results and schedules new runs.
+    fn poll_background(&mut self) {
+        // Asked for by hand, so a pause doesn't hold it up
+        if let Some(action) = &mut self.task_action {
+            if action.poll() {
+                let message = action.latest().cloned().unwrap_or_default();
+                self.task_action = None;
+                self.set_status(message);
+                self.pueue.rerun();
+            }
+        }
+        if self.paused {
+            return;
+        }
+        for failure in self.webhook.failures() {
+            self.errors.record("webhook", &failure);
+        }
+        // Failures are logged as each run's result comes in
+        if self.package_manager.is_some()
+            && self.collects(&PanelKind::Updates)
+            && self.updates.poll()
+        {
+            if let Some(Err(err)) = self.updates.latest() {
+                self.errors.record("updates", err);
+            }
+        }
+        if let Some(log) = &mut self.task_log {
+            log.output.poll();
+        }
+        if let Some(output) = &mut self.panel_action_output {
+            output.output.poll();
+        }
+        if self.collects(&PanelKind::Pueue) && self.pueue.poll() {
+            for group in self.pueue.latest().into_iter().flatten() {
+                if let Err(err) = &group.status {
+                    self.errors.record(&format!("pueue {}", group.name), err);
+                }
+            }
+        }
+        if self.config.program_check == ProgramCheck::Systemd
+            && self.collects(&PanelKind::Programs)
+            && self.services.poll()
+        {
+            if let Some(Err(err)) = self.services.latest() {
+                self.errors.record("systemd", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Users) && self.sessions.poll() {
+            if let Some(Err(err)) = self.sessions.latest() {
+                self.errors.record("users", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Docker) && self.containers.poll() {
+            if let Some(Err(err)) = self.containers.latest() {
+                self.errors.record("docker", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Gpu) && self.gpus.poll() {
+            if let Some(Err(err)) = self.gpus.latest() {
+                self.errors.record("nvidia-smi", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Logs) && self.logs.poll() {
+            if let Some(Err(err)) = self.logs.latest() {
+                self.errors.record("logs", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Ports) && self.sockets.poll() {
+            if let Some(Err(err)) = self.sockets.latest() {
+                self.errors.record("ss", err);
+            }
+        }
+        if self.config.panels.contains(&PanelKind::Ping) && self.pings.poll() {
+            if let Some(Err(err)) = self.pings.latest() {
+                self.errors.record("ping", err);
+            }
+        }
+        // Hosts that can't be reached are shown as offline in the panel
+        if self.config.panels.contains(&PanelKind::Hosts) {
+            self.hosts.poll();
+        }
+
+        for panel in &self.config.panels {
+            if let PanelKind::Command(title) = panel {
+                if let Some(index) = self.config.command_panel(title) {
+                    let output = &mut self.commands[index].output;
+                    if output.poll() {
+                        if let Some(Err(err)) = output.latest() {
+                            self.errors.record(title, err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn ui<B: Backend>(f: &mut tui::Frame<B>, app: &mut App) {
+    // Focus moves on if its panel was hidden
+    let visible = app.visible_panels();
+    if !visible.contains(&app.focused) {
+        app.focused = visible.first().copied().unwrap_or(0);
+    }
+    if app.maximized.is_some_and(|i| !visible.contains(&i)) {
+        app.maximized = None;
+    }
+
+    let config = &app.config;
+    let theme = &app.theme;
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .horizontal_margin(1)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(f.size());
+
+    let focused_position = visible.iter().position(|&i| i == app.focused).unwrap_or(0);
+    let areas = match app.maximized {
+        Some(_) => layout_panels(rows[1], &[MIN_PANEL_HEIGHT], 0, None)
+            .map(|_| vec![(focused_position, rows[1])]),
+        None => {
+            let min_heights: Vec<u16> = visible
+                .iter()
+                .map(|&i| min_panel_height(&config.panels[i]))
+                .collect();
+            layout_panels(rows[1], &min_heights, focused_position, config.columns)
+        }
+    };
+    let Some(areas) = areas else {
+        app.panel_areas.clear();
+        draw_too_small(f, theme);
+        return;
+    };
+    app.panel_areas = areas
+        .iter()
+        .map(|&(position, area)| (visible[position], area))
+        .collect();
+
+    for (position, area) in areas {
+        let i = visible[position];
+        let focused = i == app.focused;
+        let theme = &Theme {
+            borders: config
+                .panel_borders
+                .get(&config.panels[i])
+                .copied()
+                .unwrap_or(theme.borders),
+            ..*theme
+        };
+        let title = config
+            .panel_titles
+            .get(&config.panels[i])
+            .map(String::as_str);
+        let units = config.panel_number_format(&config.panels[i]);
+        match &config.panels[i] {
+            // Memory and Disk Usage
+            PanelKind::Memory => draw_memory_disk(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.source.memory(),
+                &app.disks(),
+                &app.memory_history,
+                app.usage_format,
+                config,
+            ),
+
+            // CPU Usage
+            PanelKind::Cpu => {
+                let stats = app.source.cpu();
+                draw_cpu(
+                    f,
+                    area,
+                    focused,
+                    theme,
+                    title,
+                    stats.as_ref(),
+                    app.source.cpu_history(),
+                    config.cpu_cores,
+                    config.ascii,
+                )
+            }
+
+            // Load Average
+            PanelKind::Load => draw_load_average(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                app.source.load().as_ref(),
+                app.source.core_count(),
+            ),
+
+            // Disk I/O
+            PanelKind::DiskIo => draw_disk_io(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                app.source.disk_rates(),
+                units,
+            ),
+
+            // Temperatures
+            PanelKind::Temperatures => draw_temperatures(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.source.temperatures(),
+                config.severity_markers,
+            ),
+
+            // Top Processes
+            PanelKind::Processes => draw_top_processes(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.top_processes(),
+                app.selected_process,
+                app.process_sort,
+                app.process_filter
+                    .as_ref()
+                    .map(|filter| filter.input.as_str()),
+                units,
+            ),
+
+            // Network Throughput
+            PanelKind::Network => draw_network(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                app.source.network_rates(),
+                &app.network_history,
+                app.network_totals_since,
+                config.exclude_loopback,
+                units,
+                config.ascii,
+            ),
+
+            // Host, OS and Kernel
+            PanelKind::SystemInfo => {
+                draw_system_info(f, area, focused, theme, title, &app.system_info)
+            }
+
+            // Uptime
+            PanelKind::Uptime => draw_uptime(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                app.source.uptime(),
+                app.source.boot_time(),
+            ),
+
+            // Available Updates via the system package manager
+            PanelKind::Updates => draw_package_updates(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                app.package_manager,
+                &app.updates,
+                config.list_updates.then_some(&mut app.updates_scroll),
+                units,
+                config.ascii,
+            ),
+
+            // Status of Certain Programs
+            PanelKind::Programs => match config.program_check {
+                ProgramCheck::Process => draw_program_status(
+                    f,
+                    area,
+                    focused,
+                    theme,
+                    title,
+                    &app.source.programs(&config.programs),
+                    units,
+                ),
+                ProgramCheck::Systemd => draw_service_status(
+                    f,
+                    area,
+                    focused,
+                    theme,
+                    title,
+                    &config.programs,
+                    &app.services,
+                ),
+            },
+
+            // Pueue Tasks Status
+            PanelKind::Pueue => draw_pueue_status(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &config.pueue_groups,
+                &app.pueue,
+                app.selected_task,
+                &mut app.pueue_scroll,
+                keys::label_for(&app.bindings, Action::ShowTaskLog),
+                config.ascii,
+            ),
+
+            // Logged-in Users
+            PanelKind::Users => draw_users(f, area, focused, theme, title, &app.sessions),
+
+            // Docker Containers
+            PanelKind::Docker => draw_containers(f, area, focused, theme, title, &app.containers),
+
+            // GPU Utilization
+            PanelKind::Gpu => draw_gpus(f, area, focused, theme, title, &app.gpus, config),
+            // Host Reachability
+            PanelKind::Ping => draw_pings(f, area, focused, theme, title, &app.pings),
+            // Remote Hosts over SSH
+            PanelKind::Hosts => draw_hosts(f, area, focused, theme, title, &app.hosts, config),
+            // Log Tail
+            PanelKind::Logs => draw_log_tail(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                config.log_source.as_ref(),
+                &app.logs,
+                &mut app.logs_scroll,
+                config.ascii,
+            ),
+            // Overall Status
+            PanelKind::Health => draw_health(f, area, focused, theme, title, &app.health()),
+            // Memory Pressure
+            PanelKind::MemoryPressure => draw_memory_pressure(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.pressure_history,
+                app.source.memory().swap_total > 0,
+                config,
+            ),
+            // Date and Time
+            PanelKind::Clock => draw_clock(f, area, focused, theme, title, config),
+            // Process States
+            PanelKind::ProcessStates => draw_process_states(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.source.process_states(),
+                units,
+            ),
+            // Listening Ports
+            PanelKind::Ports => draw_sockets(f, area, focused, theme, title, &app.sockets),
+
+            // Battery
+            PanelKind::Battery => draw_battery(f, area, focused, theme, title, &app.batteries),
+
+            // Errors
+            PanelKind::Errors => draw_errors(f, area, focused, theme, title, &app.errors),
+
+            // Thresholds crossed, oldest first
+            PanelKind::Alerts => draw_alerts(
+                f,
+                area,
+                focused,
+                theme,
+                title,
+                &app.alerts,
+                &mut app.alerts_scroll,
+                config.ascii,
+            ),
+
+            // Output of a user-defined command
+            PanelKind::Command(title) => {
+                if let Some(index) = config.command_panel(title) {
+                    let CommandOutput { output, scroll } = &mut app.commands[index];
+                    draw_command_panel(
+                        f,
+                        area,
+                        focused,
+                        theme,
+                        title,
+                        output,
+                        scroll,
+                        config.ascii,
+                    );
+                }
+            }
+        }
+
+        if let Some(running) = app.busy_for(&config.panels[i]) {
+            draw_spinner(f, area, theme, running, config.ascii);
+        }
+    }
+
+    draw_footer(f, rows[2], app);
+
+    if let Some(log) = &mut app.task_log {
+        let close_key = keys::label_for(&app.bindings, Action::ShowTaskLog);
+        draw_task_log(f, &app.theme, log, close_key, app.config.ascii);
+    }
+    if let Some(output) = &mut app.panel_action_output {
+        draw_action_output(f, &app.theme, output, app.config.ascii);
+    }
+    if app.show_help {
+        draw_help(f, app);
+    }
+}
+
+/// The bottom line: clock, hostname and run state on the left, and key
+/// hints, or a transient message, on the right.
+fn draw_footer<B: Backend>(f: &mut tui::Frame<B>, area: Rect, app: &App) {
+    let theme = &app.theme;
+    let state = if app.paused {
+        Span::styled(
+            " PAUSED ",
+            Style::default()
+                .fg(theme.badge_text)
+                .bg(theme.critical)
+                .add_modifier(Modifier::BOLD),
+        )
+    } else {
+        Span::styled("running", Style::default().fg(theme.good))
+    };
+    let left = Spans::from(vec![
+        Span::raw(chrono::Local::now().format("%H:%M:%S").to_string()),
+        Span::raw("  "),
+        Span::styled(
+            app.hostname.as_str(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("  "),
+        state,
+    ]);
+
+    let right = if let Some(filter) = app.process_filter.as_ref().filter(|filter| filter.editing) {
+        Span::styled(
+            format!("/{}█  Enter to keep, Esc to clear", filter.input),
+            Style::default().fg(theme.badge_text).bg(theme.warn),
+        )
+    } else if let Some(request) = &app.kill_requested {
+        Span::styled(
+            format!(
+                "Send SIGTERM to {} ({})? y to confirm",
+                request.pid, request.name
+            ),
+            Style::default().fg(theme.badge_text).bg(theme.critical),
+        )
+    } else if let Some(action) = &app.panel_action_requested {
+        Span::styled(
+            format!("Run `{}`? y to confirm", action.command_line()),
+            Style::default().fg(theme.badge_text).bg(theme.critical),
+        )
+    } else if let Some(request) = &app.task_action_requested {
+        Span::styled(
+            format!(
+                "{} pueue task {}? y to confirm",
+                request.action.verb(),
+                request.id
+            ),
+            Style::default().fg(theme.badge_text).bg(theme.warn),
+        )
+    } else if app.is_confirming_quit() {
+        Span::styled(
+            format!("Press {} again to quit", app.config.quit_key),
+            Style::default().fg(theme.badge_text).bg(theme.warn),
+        )
+    } else if let Some(message) = app.status() {
+        Span::styled(message, Style::default().fg(theme.warn))
+    } else if !app.errors.is_empty() {
+        let count = app.errors.entries().len();
+        Span::styled(
+            format!(
+                "⚠ {} error{}  {} quit  ? help",
+                count,
+                if count == 1 { "" } else { "s" },
+                app.config.quit_key
+            ),
+            Style::default().fg(theme.critical),
+        )
+    } else {
+        Span::styled(
+            format!("{} quit  ? help", app.config.quit_key),
+            Style::default().fg(theme.muted),
+        )
+    };
+
+    f.render_widget(
+        Paragraph::new(left).style(Style::default().fg(theme.text)),
+        area,
+    );
+    f.render_widget(
+        Paragraph::new(Spans::from(right)).alignment(Alignment::Right),
+        area,
+    );
+}
+
+/// Lists every keybinding in a box centered over the dashboard, followed by
+/// the panels the number keys toggle.
+fn draw_help<B: Backend>(f: &mut tui::Frame<B>, app: &App) {
+    let theme = &app.theme;
+    // Keys bound to the same action share a line, in table order
+    let mut entries: Vec<(Action, Vec<String>)> = Vec::new();
+    let mut panel_keys = Vec::new();
+    for binding in &app.bindings {
+        if let Action::TogglePanel(index) = binding.action {
+            panel_keys.push((binding.label(), index));
+            continue;
+        }
+        match entries
+            .iter_mut()
+            .find(|(action, _)| *action == binding.action)
+        {
+            Some((_, labels)) => labels.push(binding.label()),
+            None => entries.push((binding.action, vec![binding.label()])),
+        }
+    }
+
+    let mut lines: Vec<Spans> = entries
+        .iter()
+        .map(|(action, labels)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:<16}", labels.join(", ")),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(action.description()),
+            ])
+        })
+        .collect();
+
+    let panels: Vec<_> = panel_keys
+        .into_iter()
+        .filter_map(|(label, index)| Some((label, index, app.config.panels.get(index)?)))
+        .collect();
+    if !panels.is_empty() {
+        lines.push(Spans::default());
+        lines.push(Spans::from(Span::styled(
+            "Panels",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    for (label, index, panel) in panels {
+        let (state, color) = if app.hidden.contains(&index) {
+            ("hidden", theme.muted)
+        } else {
+            ("shown", theme.good)
+        };
+        lines.push(Spans::from(vec![
+            Span::styled(
+                format!("{:<16}", label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("{:<24}", panel.label())),
+            Span::styled(state, Style::default().fg(color)),
+        ]));
+    }
+
+    let actions: Vec<_> = app
+        .config
+        .command_panels
+        .iter()
+        .flat_map(|panel| panel.actions.iter().map(move |action| (panel, action)))
+        .collect();
+    if !actions.is_empty() {
+        lines.push(Spans::default());
+        lines.push(Spans::from(Span::styled(
+            "Command panel actions",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    for (panel, action) in actions {
+        lines.push(Spans::from(vec![
+            Span::styled(
+                format!("{:<16}", action.key),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("{}: {}", panel.title, action.command_line())),
+        ]));
+    }
+
+    let size = f.size();
+    let width = 60.min(size.width);
+    let height = (lines.len() as u16 + 2).min(size.height);
+    let area = Rect::new(
+        (size.width - width) / 2,
+        (size.height - height) / 2,
+        width,
+        height,
+    );
+
+    let paragraph = Paragraph::new(lines)
+        .block(panel_block("Help (? or Esc to close)", true, theme))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+/// The fewest rows a panel is still useful in, borders included.
+fn min_panel_height(panel: &PanelKind) -> u16 {
+    match panel {
+        // A gauge and at least one disk, or a header and one process
+        PanelKind::Memory | PanelKind::Processes => MIN_PANEL_HEIGHT + 1,
+        _ => MIN_PANEL_HEIGHT,
+    }
+}
+
+fn draw_too_small<B: Backend>(f: &mut tui::Frame<B>, theme: &Theme) {
+    let paragraph = Paragraph::new("Terminal too small")
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(theme.critical));
+
+    f.render_widget(paragraph, f.size());
+}
+
+/// A panel with nothing to show, saying so in the middle of its frame
+/// rather than leaving it blank.
+fn draw_empty<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    block: Block,
+    theme: &Theme,
+    message: &str,
+) {
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Long messages wrap onto the lines below the middle one
+    let middle = inner.y + inner.height.saturating_sub(1) / 2;
+    let paragraph = Paragraph::new(message)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(theme.muted))
+        .wrap(Wrap { trim: true });
+    f.render_widget(
+        paragraph,
+        Rect {
+            y: middle,
+            height: inner.bottom() - middle,
+            ..inner
+        },
+    );
+}
+
+/// The bordered frame around a panel, highlighted while the panel has focus.
+fn panel_block<'a>(title: impl Into<Spans<'a>>, focused: bool, theme: &Theme) -> Block<'a> {
+    let border_style = if focused {
+        Style::default()
+            .fg(theme.focused_border)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.border)
+    };
+
+    let mut title = title.into();
+    for span in &mut title.0 {
+        span.style = Style::default().fg(theme.title).patch(span.style);
+    }
+    let block = Block::default();
+    match theme.borders.border_type() {
+        Some(border_type) => block
+            .title(title)
+            .borders(Borders::ALL)
+            .border_type(border_type)
+            .border_style(border_style),
+        // Without a frame to highlight, the title shows the focus instead
+        None if focused => {
+            for span in &mut title.0 {
+                span.style = span.style.patch(border_style);
+            }
+            block.title(title)
+        }
+        None => block.title(title),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_memory_disk<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    memory: &MemoryStats,
+    disks: &[DiskStats],
+    history: &History,
+    usage: UsageFormat,
+    config: &Config,
+) {
+    let block = panel_block(title.unwrap_or("Memory and Disk Usage"), focused, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let units = config.panel_number_format(&PanelKind::Memory);
+    let disk_units = NumberFormat {
+        byte_units: config.disk_byte_units.unwrap_or(units.byte_units),
+        ..units
+    };
+    let markers = config.severity_markers;
+    let memory_percent = percent(memory.used, memory.total);
+    let memory_usage = with_marker(
+        format!(
+            "Memory Usage: {}",
+            format_usage(memory.used, memory.total, units, usage)
+        ),
+        usage_level(
+            memory_percent,
+            config.thresholds.memory_warn,
+            config.thresholds.memory_critical,
+        ),
+        markers,
+    );
+
+    // The disks come right after the memory gauge in importance, then what
+    // is available, and swap and the history only get a line if there is
+    // room left over for them
+    let disk_lines = disks
+        .iter()
+        .map(|disk| 1 + usize::from(disk.inodes.is_some()))
+        .sum::<usize>()
+        .max(1) as u16;
+    let spare = inner.height.saturating_sub(1 + disk_lines);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(u16::from(spare >= 3)),
+            Constraint::Length(u16::from(spare >= 1)),
+            Constraint::Length(u16::from(spare >= 2)),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+
+    let color = gauge_color(
+        memory_percent,
+        config.thresholds.memory_warn,
+        config.thresholds.memory_critical,
+        theme,
+    );
+    draw_usage_gauge(
+        f,
+        rows[0],
+        &memory_usage,
+        memory_percent,
+        color,
+        theme.gauge_background,
+        config.text_gauges,
+    );
+    draw_history(f, rows[1], history, color, config.ascii);
+
+    // Used memory includes caches the kernel drops under pressure, so
+    // what is available says more about how close an OOM is
+    let mut available = vec![
+        Span::raw("Available: "),
+        Span::styled(
+            format_bytes(memory.available, units),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+    ];
+    let reclaimable: Vec<String> = [("cached", memory.cached), ("buffers", memory.buffers)]
+        .into_iter()
+        .filter_map(|(name, bytes)| Some(format!("{} {}", name, format_bytes(bytes?, units))))
+        .collect();
+    if !reclaimable.is_empty() {
+        available.push(Span::styled(
+            format!("  ({})", reclaimable.join(", ")),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    let paragraph = Paragraph::new(Spans::from(available)).style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, rows[2]);
+
+    if memory.swap_total == 0 {
+        let paragraph = Paragraph::new("Swap: disabled").style(Style::default().fg(theme.text));
+        f.render_widget(paragraph, rows[3]);
+    } else {
+        let swap_percent = percent(memory.swap_used, memory.swap_total);
+        let mut swap_usage = format!(
+            "Swap: {}",
+            format_usage(memory.swap_used, memory.swap_total, units, usage)
+        );
+        if usage != UsageFormat::Percent {
+            swap_usage.push_str(&format!(", {} free", format_bytes(memory.swap_free, units)));
+        }
+        let swap_level = usage_level(
+            swap_percent,
+            config.thresholds.swap_warn,
+            config.thresholds.swap_critical,
+        );
+        // Swapping heavily is often the first sign of memory pressure
+        if markers {
+            swap_usage = with_marker(swap_usage, swap_level, markers);
+        } else if swap_level != Level::Normal {
+            swap_usage.push_str(" ⚠");
+        }
+
+        let color = gauge_color(
+            swap_percent,
+            config.thresholds.swap_warn,
+            config.thresholds.swap_critical,
+            theme,
+        );
+        draw_usage_gauge(
+            f,
+            rows[3],
+            &swap_usage,
+            swap_percent,
+            color,
+            theme.gauge_background,
+            config.text_gauges,
+        );
+    }
+
+    let disk_color = |used, total| {
+        gauge_color(
+            percent(used, total),
+            config.thresholds.disk_warn,
+            config.thresholds.disk_critical,
+            theme,
+        )
+    };
+    let disk_marker = |text: String, used, total| {
+        let level = usage_level(
+            percent(used, total),
+            config.thresholds.disk_warn,
+            config.thresholds.disk_critical,
+        );
+        with_marker(text, level, markers)
+    };
+    let mut lines = Vec::new();
+    for disk in disks {
+        lines.push(Spans::from(Span::styled(
+            disk_marker(
+                format!(
+                    "{} ({}): {} / {}",
+                    disk.mount_point,
+                    disk.file_system,
+                    format_bytes(disk.used, disk_units),
+                    format_bytes(disk.total, disk_units)
+                ),
+                disk.used,
+                disk.total,
+            ),
+            Style::default().fg(disk_color(disk.used, disk.total)),
+        )));
+        if let Some(inodes) = &disk.inodes {
+            lines.push(Spans::from(Span::styled(
+                disk_marker(
+                    format!(
+                        "  Inodes: {} / {} ({:.0}%)",
+                        format_count(inodes.used, units),
+                        format_count(inodes.total, units),
+                        percent(inodes.used, inodes.total)
+                    ),
+                    inodes.used,
+                    inodes.total,
+                ),
+                Style::default().fg(disk_color(inodes.used, inodes.total)),
+            )));
+        }
+    }
+
+    if disks.is_empty() {
+        lines.push(Spans::from("No disks detected"));
+    }
+
+    let paragraph = Paragraph::new(lines).style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, rows[4]);
+}
+
+/// A sparkline of recent percentages, as many as fit, newest on the right.
+fn draw_history<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    history: &History,
+    color: Color,
+    ascii: bool,
+) {
+    let samples = history.latest(area.width as usize);
+    let sparkline = Sparkline::default()
+        .data(&samples)
+        .max(100)
+        .bar_set(if ascii { ASCII_BARS } else { bar::NINE_LEVELS })
+        .style(Style::default().fg(color));
+
+    f.render_widget(sparkline, area);
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64 * 100.0
+    }
+}
+
+/// Normal below `warn`, warning below `critical`, critical from there on.
+fn usage_level(percent: f64, warn: f64, critical: f64) -> Level {
+    if percent >= critical {
+        Level::Critical
+    } else if percent >= warn {
+        Level::Warn
+    } else {
+        Level::Normal
+    }
+}
+
+/// Good below `warn`, warning below `critical`, critical from there on.
+fn usage_color(percent: f64, warn: f64, critical: f64, theme: &Theme) -> Color {
+    match usage_level(percent, warn, critical) {
+        Level::Critical => theme.critical,
+        Level::Warn => theme.warn,
+        Level::Normal => theme.good,
+    }
+}
+
+/// `text` with the marker for its level appended, with `severity_markers`.
+fn with_marker(mut text: String, level: Level, markers: bool) -> String {
+    if markers {
+        text.push(' ');
+        text.push_str(level.marker());
+    }
+    text
+}
+
+/// Like `usage_color`, but with `theme.gradient` a point on a smooth
+/// gradient that turns from green to yellow at `warn` and on to red at
+/// `critical`.
+fn gauge_color(percent: f64, warn: f64, critical: f64, theme: &Theme) -> Color {
+    const GREEN: [u8; 3] = [0x4c, 0xaf, 0x50];
+    const YELLOW: [u8; 3] = [0xff, 0xc1, 0x07];
+    const RED: [u8; 3] = [0xf4, 0x43, 0x36];
+    if !theme.gradient {
+        return usage_color(percent, warn, critical, theme);
+    }
+
+    // Thresholds of 0, or a warning level equal to the critical one, leave
+    // nothing to blend over
+    let ratio = |value: f64, span: f64| {
+        if span > 0.0 {
+            (value / span).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    };
+    let (from, to, t) = if percent < warn {
+        (GREEN, YELLOW, ratio(percent, warn))
+    } else {
+        (YELLOW, RED, ratio(percent - warn, critical - warn))
+    };
+    let [r, g, b] =
+        [0, 1, 2].map(|i| (from[i] as f64 + (to[i] as f64 - from[i] as f64) * t).round() as u8);
+    Color::Rgb(r, g, b)
+}
+
+/// A one-line gauge filled to `percent` under `label`, or only the label as
+/// plain text.
+fn draw_usage_gauge<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    label: &str,
+    percent: f64,
+    color: Color,
+    background: Color,
+    text_only: bool,
+) {
+    if text_only {
+        let paragraph = Paragraph::new(label).style(Style::default().fg(color));
+        f.render_widget(paragraph, area);
+    } else {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color).bg(background))
+            .ratio((percent / 100.0).clamp(0.0, 1.0))
+            .label(label);
+        f.render_widget(gauge, area);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_cpu<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    stats: Option<&CpuStats>,
+    history: &History,
+    view: CpuCoreView,
+    ascii: bool,
+) {
+    let block = panel_block(title.unwrap_or("CPU Usage"), focused, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(inner);
+
+    // `None` until warmed up, the first sample only establishes a baseline
+    // and usage would read 0%
+    let Some(stats) = stats else {
+        let paragraph =
+            Paragraph::new("CPU Usage: measuring...").style(Style::default().fg(theme.text));
+        f.render_widget(paragraph, inner);
+        return;
+    };
+
+    let mut usage = vec![Span::raw(format!("CPU Usage: {:.1}%", stats.usage))];
+    // On a VM, time the hypervisor gave to others is CPU this one didn't get
+    if let Some(steal) = stats.steal {
+        let style = if steal >= 0.05 {
+            Style::default().fg(theme.warn)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        usage.push(Span::styled(format!("  steal {:.1}%", steal), style));
+    }
+    if let Some(throttled) = stats.throttled.filter(|&throttled| throttled > 0) {
+        usage.push(Span::styled(
+            format!("  ⚠ throttled {}×", throttled),
+            Style::default().fg(theme.critical),
+        ));
+    }
+    let paragraph = Paragraph::new(Spans::from(usage)).style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, rows[0]);
+    draw_history(f, rows[1], history, theme.good, ascii);
+
+    if view == CpuCoreView::Bars {
+        draw_core_bars(f, rows[2], theme, &stats.cores, ascii);
+        return;
+    }
+
+    let mut text = String::new();
+    for (i, &usage) in stats.cores.iter().enumerate() {
+        // Virtual machines and some ARM boards don't expose the clock speed
+        let frequency = match stats.frequencies.get(i) {
+            Some(&mhz) if mhz > 0 => format_frequency(mhz),
+            _ => "—".to_string(),
+        };
+        text.push_str(&format!(
+            "cpu{:<3} {} {:5.1}%  {:>9}\n",
+            i,
+            usage_bar(usage, CPU_BAR_WIDTH, ascii),
+            usage,
+            frequency
+        ));
+    }
+
+    let paragraph = Paragraph::new(text).style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, rows[2]);
+}
+
+/// One bar per core, labeled with its index and colored by how busy it is,
+/// wrapped onto as many rows as it takes. Cores that don't fit even then are
+/// left out.
+fn draw_core_bars<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    theme: &Theme,
+    cores: &[f32],
+    ascii: bool,
+) {
+    // A bar, its value and its label need three lines
+    const BAR_WIDTH: u16 = 3;
+    const MIN_ROW_HEIGHT: u16 = 3;
+    if cores.is_empty() || area.height < MIN_ROW_HEIGHT || area.width < BAR_WIDTH {
+        return;
+    }
+
+    let per_row = ((area.width + 1) / (BAR_WIDTH + 1)) as usize;
+    let row_count = cores
+        .len()
+        .div_ceil(per_row)
+        .min((area.height / MIN_ROW_HEIGHT) as usize);
+    let row_height = area.height / row_count as u16;
+
+    for (i, &usage) in cores.iter().take(per_row * row_count).enumerate() {
+        let color = usage_color(usage as f64, 75.0, 90.0, theme);
+        let label = i.to_string();
+        let data = [(label.as_str(), usage.round() as u64)];
+        let chart = BarChart::default()
+            .data(&data)
+            .max(100)
+            .bar_width(BAR_WIDTH)
+            .bar_gap(0)
+            .bar_set(if ascii { ASCII_BARS } else { bar::NINE_LEVELS })
+            .bar_style(Style::default().fg(color))
+            .value_style(Style::default().fg(theme.badge_text).bg(color))
+            .label_style(Style::default().fg(theme.muted));
+        let bar_area = Rect::new(
+            area.x + (i % per_row) as u16 * (BAR_WIDTH + 1),
+            area.y + (i / per_row) as u16 * row_height,
+            BAR_WIDTH,
+            row_height,
+        );
+        f.render_widget(chart, bar_area);
+    }
+}
+
+/// Renders `percent` as a fixed-width bar of block characters, or of `#`
+/// and `.` in ASCII.
+fn usage_bar(percent: f32, width: usize, ascii: bool) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f32).round() as usize;
+    let (full, empty) = if ascii { ("#", ".") } else { ("█", "░") };
+    format!("{}{}", full.repeat(filled), empty.repeat(width - filled))
+}
+
+fn draw_load_average<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    load: Option<&LoadStats>,
+    cores: usize,
+) {
+    let text = if let Some(load) = load {
+        let cores = cores.max(1) as f64;
+
+        let mut spans = vec![Span::raw("Load Average: ")];
+        for (label, value) in [("1m", load.one), ("5m", load.five), ("15m", load.fifteen)] {
+            let color = if value < cores {
+                theme.good
+            } else if value < cores * 2.0 {
+                theme.warn
+            } else {
+                theme.critical
+            };
+            spans.push(Span::styled(
+                format!("{:.2}", value),
+                Style::default().fg(color),
+            ));
+            spans.push(Span::raw(format!(" ({})  ", label)));
+        }
+        Spans::from(spans)
+    } else {
+        Spans::from("Load Average: N/A")
+    };
+
+    let paragraph =
+        Paragraph::new(text).block(panel_block(title.unwrap_or("Load Average"), focused, theme));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_disk_io<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    rates: &[DiskRate],
+    units: NumberFormat,
+) {
+    let block = panel_block(title.unwrap_or("Disk I/O"), focused, theme);
+    if rates.is_empty() {
+        return draw_empty(f, area, block, theme, "No disks detected");
+    }
+
+    let mut text = String::new();
+    for rate in rates {
+        text.push_str(&format!(
+            "{:<16} R {:>10}  W {:>10}\n",
+            rate.name,
+            format_rate(rate.read, units),
+            format_rate(rate.written, units)
+        ));
+    }
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_temperatures<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    sensors: &[TemperatureStats],
+    markers: bool,
+) {
+    let block = panel_block(title.unwrap_or("Temperatures"), focused, theme);
+    if sensors.is_empty() {
+        return draw_empty(f, area, block, theme, "No sensors available");
+    }
+
+    let mut lines = Vec::new();
+    for sensor in sensors {
+        let (temperature, critical) = (sensor.temperature, sensor.critical);
+        let heat = (temperature / critical.unwrap_or(DEFAULT_CRITICAL_TEMPERATURE)) as f64 * 100.0;
+        let color = usage_color(heat, 85.0, 100.0, theme);
+
+        let mut spans = vec![
+            Span::raw(format!("{:<24} ", sensor.label)),
+            Span::styled(
+                with_marker(
+                    format!("{:>5.1}°C", temperature),
+                    usage_level(heat, 85.0, 100.0),
+                    markers,
+                ),
+                Style::default().fg(color),
+            ),
+        ];
+        if let Some(critical) = critical {
+            spans.push(Span::raw(format!("  (critical {:.0}°C)", critical)));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    let paragraph = Paragraph::new(lines).block(block);
+
+    f.render_widget(paragraph, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_network<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    rates: &[InterfaceRate],
+    history: &(History, History),
+    totals_since: chrono::DateTime<chrono::Local>,
+    exclude_loopback: bool,
+    units: NumberFormat,
+    ascii: bool,
+) {
+    let block = panel_block(title.unwrap_or("Network Throughput"), focused, theme);
+    let inner = block.inner(area);
+
+    let mut text = String::new();
+    let mut session = (0, 0);
+    let mut total = (0, 0);
+    for rate in rates {
+        if exclude_loopback && is_loopback(&rate.name) {
+            continue;
+        }
+        text.push_str(&format!(
+            "{:<12} ↓ {:>10}  ↑ {:>10}\n",
+            rate.name,
+            format_rate(rate.received, units),
+            format_rate(rate.transmitted, units)
+        ));
+        session.0 += rate.session_received;
+        session.1 += rate.session_transmitted;
+        total.0 += rate.total_received;
+        total.1 += rate.total_transmitted;
+    }
+
+    if text.is_empty() {
+        return draw_empty(f, area, block, theme, "No network interfaces detected");
+    }
+    f.render_widget(block, area);
+    text.push_str(&format!(
+        "Since {}: ↓ {}  ↑ {}  (since boot ↓ {}  ↑ {})\n",
+        totals_since.format("%H:%M"),
+        format_bytes(session.0, units),
+        format_bytes(session.1, units),
+        format_bytes(total.0, units),
+        format_bytes(total.1, units)
+    ));
+
+    // The chart only gets drawn below the rates if it can show something
+    let lines = text.lines().count() as u16;
+    let chart_height = inner.height.saturating_sub(lines);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(lines),
+            Constraint::Length(if chart_height >= 5 { chart_height } else { 0 }),
+        ])
+        .split(inner);
+
+    let paragraph = Paragraph::new(text).style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, rows[0]);
+    if rows[1].height > 0 {
+        draw_network_chart(f, rows[1], theme, history, units, ascii);
+    }
+}
+
+/// Line graph of the received and transmitted rates, scaled to the highest
+/// rate in view.
+fn draw_network_chart<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    theme: &Theme,
+    (received, transmitted): &(History, History),
+    units: NumberFormat,
+    ascii: bool,
+) {
+    // Newest on the right, so a history that is still filling up grows
+    // in from there like the sparklines do
+    let samples = received.capacity().max(2);
+    let points = |history: &History| -> Vec<(f64, f64)> {
+        let rates = history.latest(samples);
+        let start = samples - rates.len();
+        rates
+            .into_iter()
+            .enumerate()
+            .map(|(i, rate)| ((start + i) as f64, rate as f64))
+            .collect()
+    };
+    let received = points(received);
+    let transmitted = points(transmitted);
+
+    let peak = received
+        .iter()
+        .chain(&transmitted)
+        .map(|&(_, rate)| rate)
+        .fold(1.0, f64::max);
+
+    let (marker, legend) = if ascii {
+        (Marker::Dot, ["rx", "tx"])
+    } else {
+        (Marker::Braille, ["↓ rx", "↑ tx"])
+    };
+    let datasets = vec![
+        Dataset::default()
+            .name(legend[0])
+            .marker(marker)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.good))
+            .data(&received),
+        Dataset::default()
+            .name(legend[1])
+            .marker(marker)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(theme.warn))
+            .data(&transmitted),
+    ];
+    let chart = Chart::new(datasets)
+        .style(Style::default().fg(theme.text))
+        // The legend is worth keeping on all but the smallest charts
+        .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 1)))
+        .x_axis(Axis::default().bounds([0.0, (samples - 1) as f64]))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(theme.muted))
+                .bounds([0.0, peak])
+                .labels(vec![
+                    Span::raw(format_rate(0.0, units)),
+                    Span::raw(format_rate(peak, units)),
+                ]),
+        );
+
+    f.render_widget(chart, area);
+}
+
+fn is_loopback(interface: &str) -> bool {
+    interface == "lo" || interface == "lo0"
+}
+
+fn draw_clock<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    config: &Config,
+) {
+    let now = chrono::Utc::now();
+    let mut time = String::new();
+    // An invalid format string fails here, rather than panicking in `to_string`
+    let formatted = match config.clock_timezone {
+        Some(timezone) => write!(
+            time,
+            "{}",
+            now.with_timezone(&timezone).format(&config.clock_format)
+        ),
+        None => write!(
+            time,
+            "{}",
+            now.with_timezone(&chrono::Local)
+                .format(&config.clock_format)
+        ),
+    };
+    let (text, color) = match formatted {
+        Ok(()) => (time, theme.text),
+        Err(_) => (
+            format!("Invalid clock_format: {}", config.clock_format),
+            theme.critical,
+        ),
+    };
+
+    let title = match (title, config.clock_timezone) {
+        (Some(title), _) => title.to_string(),
+        (None, Some(timezone)) => format!("Clock ({})", timezone.name()),
+        (None, None) => "Clock".to_string(),
+    };
+    let paragraph = Paragraph::new(text)
+        .block(panel_block(title, focused, theme))
+        .style(Style::default().fg(color))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+/// One line for the worst reading, colored by how bad it is, and how many
+/// more there are.
+fn draw_health<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    problems: &[(Level, String)],
+) {
+    let (text, color) = match problems {
+        [] => ("OK  all within their thresholds".to_string(), theme.good),
+        [(level, reason), rest @ ..] => {
+            let (verdict, color) = match level {
+                Level::Critical => ("CRITICAL", theme.critical),
+                _ => ("WARNING", theme.warn),
+            };
+            let mut text = format!("{}  {}", verdict, reason);
+            if !rest.is_empty() {
+                text.push_str(&format!(" (+{} more)", rest.len()));
+            }
+            (text, color)
+        }
+    };
+
+    let block = panel_block(title.unwrap_or("System Health"), focused, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let paragraph = Paragraph::new(Span::styled(
+        text,
+        Style::default().add_modifier(Modifier::BOLD),
+    ))
+    .style(Style::default().fg(theme.badge_text).bg(color));
+    f.render_widget(paragraph, Rect { height: 1, ..inner });
+}
+
+/// Samples looked at for whether memory pressure is rising.
+const PRESSURE_WINDOW: usize = 10;
+/// Share of memory that must be out of reach before pressure is reported.
+const PRESSURE_UNAVAILABLE: u64 = 90;
+/// Rise in unavailable memory and in swap, in points, over the window.
+const PRESSURE_RISE: u64 = 5;
+
+/// Whether memory is running out: the share of it that isn't available
+/// rose steadily to near all of it over the last samples, while swap filled
+/// up too. Kept strict, since a single spike or a big cache isn't a reason
+/// for alarm.
+fn memory_pressure_rising(unavailable: &[u64], swap: &[u64]) -> bool {
+    if unavailable.len() < PRESSURE_WINDOW || swap.len() < PRESSURE_WINDOW {
+        return false;
+    }
+    let unavailable = &unavailable[unavailable.len() - PRESSURE_WINDOW..];
+    let swap = &swap[swap.len() - PRESSURE_WINDOW..];
+    let rise = |samples: &[u64]| samples[samples.len() - 1].saturating_sub(samples[0]);
+    // Falling back by more than a point on the way means it isn't a trend
+    let steady = unavailable.windows(2).all(|pair| pair[1] + 1 >= pair[0]);
+
+    unavailable[PRESSURE_WINDOW - 1] >= PRESSURE_UNAVAILABLE
+        && steady
+        && rise(unavailable) >= PRESSURE_RISE
+        && rise(swap) >= PRESSURE_RISE
+}
+
+/// Sparklines of the memory that isn't available and of swap, under a line
+/// warning when both have been rising toward an OOM.
+#[allow(clippy::too_many_arguments)]
+fn draw_memory_pressure<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    (unavailable, swap): &(History, History),
+    swap_enabled: bool,
+    config: &Config,
+) {
+    let block = panel_block(title.unwrap_or("Memory Pressure"), focused, theme);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let samples = (
+        unavailable.latest(PRESSURE_WINDOW),
+        swap.latest(PRESSURE_WINDOW),
+    );
+    let latest = |samples: &[u64]| samples.last().copied().unwrap_or(0);
+    let thresholds = &config.thresholds;
+    let unavailable_color = usage_color(
+        latest(&samples.0) as f64,
+        thresholds.memory_warn,
+        thresholds.memory_critical,
+        theme,
+    );
+    let swap_color = usage_color(
+        latest(&samples.1) as f64,
+        thresholds.swap_warn,
+        thresholds.swap_critical,
+        theme,
+    );
+
+    let mut spans = vec![Span::styled(
+        format!("Unavailable: {}%", latest(&samples.0)),
+        Style::default().fg(unavailable_color),
+    )];
+    if swap_enabled {
+        spans.push(Span::styled(
+            format!("  Swap: {}%", latest(&samples.1)),
+            Style::default().fg(swap_color),
+        ));
+    }
+    if swap_enabled && memory_pressure_rising(&samples.0, &samples.1) {
+        spans.push(Span::styled(
+            "  ⚠ memory pressure rising",
+            Style::default()
+                .fg(theme.critical)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    let paragraph = Paragraph::new(Spans::from(spans)).style(Style::default().fg(theme.text));
+    f.render_widget(paragraph, Rect { height: 1, ..inner });
+
+    // Swap gets the lower half of the graphs, where there is any
+    let graphs = Rect {
+        y: inner.y + 1,
+        height: inner.height.saturating_sub(1),
+        ..inner
+    };
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(if swap_enabled {
+            [Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)]
+        } else {
+            [Constraint::Min(0), Constraint::Length(0)]
+        })
+        .split(graphs);
+    draw_history(f, rows[0], unavailable, unavailable_color, config.ascii);
+    if swap_enabled {
+        draw_history(f, rows[1], swap, swap_color, config.ascii);
+    }
+}
+
+fn draw_uptime<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    uptime_seconds: u64,
+    boot_time: u64,
+) {
+    let mut lines = vec![Spans::from(format!(
+        "Uptime: {}",
+        format_uptime(uptime_seconds)
+    ))];
+    // Zero where the OS wouldn't say
+    if let Some(booted) =
+        chrono::DateTime::from_timestamp(boot_time as i64, 0).filter(|_| boot_time > 0)
+    {
+        lines.push(Spans::from(format!(
+            "Booted: {}",
+            booted
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(panel_block(
+            title.unwrap_or("System Uptime"),
+            focused,
+            theme,
+        ))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_system_info<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    info: &SystemInfo,
+) {
+    let field = |label: &str, value: Option<&str>| {
+        let value = match value.filter(|value| !value.is_empty()) {
+            Some(value) => Span::raw(value.to_string()),
+            None => Span::styled("unknown", Style::default().fg(theme.muted)),
+        };
+        Spans::from(vec![Span::raw(format!("{:<10}", label)), value])
+    };
+    let lines = vec![
+        field("Hostname", info.hostname.as_deref()),
+        field("OS", info.os.as_deref()),
+        field("Kernel", info.kernel.as_deref()),
+        field("Arch", Some(&info.arch)),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .block(panel_block(title.unwrap_or("System Info"), focused, theme))
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_package_updates<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    manager: Option<PackageManager>,
+    updates: &Background<Result<Vec<PackageUpdate>, String>>,
+    // Only when listing the packages, not just counting them
+    scroll: Option<&mut Scroll>,
+    format: NumberFormat,
+    ascii: bool,
+) {
+    let Some(manager) = manager else {
+        let block = panel_block(title.unwrap_or("Package Updates"), focused, theme);
+        return draw_empty(f, area, block, theme, "No supported package manager found");
+    };
+
+    let mut text = vec![Span::raw(match updates.latest() {
+        Some(Ok(packages)) => format!(
+            "Available Updates: {}",
+            format_count(packages.len() as u64, format)
+        ),
+        Some(Err(_)) => "Available Updates: unknown (couldn't determine)".to_string(),
+        None => "Available Updates: checking…".to_string(),
+    })];
+    // Counted only every so often, so say how old the count is
+    if let Some(age) = updates.age() {
+        text.push(Span::styled(
+            format!("  (updated {})", format_age(age)),
+            Style::default().fg(theme.muted),
+        ));
+    }
+    let mut lines = vec![Spans::from(text)];
+
+    let default_title = format!("{} Updates", manager.name());
+    let block = panel_block(
+        stale_title(title.unwrap_or(&default_title), updates),
+        focused,
+        theme,
+    );
+    let Some(scroll) = scroll else {
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(theme.text));
+        f.render_widget(paragraph, area);
+        return;
+    };
+
+    if let Some(Ok(packages)) = updates.latest() {
+        // A few long names shouldn't push every version off the panel
+        let width = packages
+            .iter()
+            .map(|package| package.name.len())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+        lines.extend(packages.iter().map(|package| {
+            let mut spans = vec![Span::raw(format!("{:<width$}  ", package.name))];
+            if let Some(from) = &package.from {
+                spans.push(Span::styled(
+                    format!("{} → ", from),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+            spans.push(Span::styled(
+                package.to.as_str(),
+                Style::default().fg(theme.good),
+            ));
+            Spans::from(spans)
+        }));
+    }
+
+    let inner = block.inner(area);
+    let offset = scroll.update(lines.len() as u16, inner.height);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .scroll((offset, 0));
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, scroll, ascii);
+}
+
+fn draw_program_status<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    programs: &[ProgramStats],
+    units: NumberFormat,
+) {
+    let block = panel_block(title.unwrap_or("Program Status"), focused, theme);
+    if programs.is_empty() {
+        return draw_empty(
+            f,
+            area,
+            block,
+            theme,
+            "No programs configured, list them in programs",
+        );
+    }
+
+    let mut statuses = Vec::new();
+
+    for program in programs {
+        let mut spans = vec![Span::raw(format!("{}: ", program.name))];
+        match program.first_pid {
+            Some(first) => {
+                let instances = match program.processes {
+                    1 => format!("PID {}", first),
+                    count => format!("{} processes, PID {}…", count, first),
+                };
+
+                spans.push(Span::styled("Running", Style::default().fg(theme.good)));
+                spans.push(Span::raw(format!(
+                    "  {}  CPU {:.1}%  RSS {}",
+                    instances,
+                    program.cpu,
+                    format_bytes(program.memory, units)
+                )));
+            }
+            None => spans.push(Span::styled(
+                "Not Running",
+                Style::default().fg(theme.critical),
+            )),
+        }
+        statuses.push(Spans::from(spans));
+    }
+
+    let paragraph = Paragraph::new(statuses)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// A login session, as listed by `who`.
+struct Session {
+    user: String,
+    tty: String,
+    /// Where the login came from, for remote sessions.
+    host: Option<String>,
+}
+
+impl Session {
+    /// Local X displays show up as hosts like `:0`, anything else came in
+    /// over the network, which in practice means SSH.
+    fn is_remote(&self) -> bool {
+        self.host
+            .as_ref()
+            .is_some_and(|host| !host.starts_with(':'))
+    }
+}
+
+fn logged_in_sessions() -> Result<Vec<Session>, String> {
+    let output = Command::new("who")
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "who not found".to_string(),
+            _ => format!("Failed to execute who: {}", err),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("who failed ({}): {}", output.status, stderr.trim()));
+    }
+
+    // alice    pts/0        2024-05-01 10:00 (192.168.1.5)
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let sessions = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let user = fields.next()?.to_string();
+            let tty = fields.next()?.to_string();
+            let host = line
+                .trim_end()
+                .strip_suffix(')')
+                .and_then(|rest| rest.rsplit_once('('))
+                .map(|(_, host)| host.to_string());
+            Some(Session { user, tty, host })
+        })
+        .collect();
+    Ok(sessions)
+}
+
+fn draw_users<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    sessions: &Background<Result<Vec<Session>, String>>,
+) {
+    let block = panel_block(
+        stale_title(title.unwrap_or("Users"), sessions),
+        focused,
+        theme,
+    );
+    let lines: Vec<Spans> = match sessions.latest() {
+        Some(Ok(sessions)) if sessions.is_empty() => {
+            return draw_empty(f, area, block, theme, "No users logged in")
+        }
+        Some(Ok(sessions)) => {
+            let mut users: Vec<&str> = sessions.iter().map(|s| s.user.as_str()).collect();
+            users.sort_unstable();
+            users.dedup();
+            let ssh = sessions.iter().filter(|s| s.is_remote()).count();
+
+            let mut lines = vec![Spans::from(vec![
+                Span::raw(format!(
+                    "{} user{}, ",
+                    users.len(),
+                    if users.len() == 1 { "" } else { "s" }
+                )),
+                Span::styled(
+                    format!("{} SSH session{}", ssh, if ssh == 1 { "" } else { "s" }),
+                    Style::default().fg(if ssh > 0 { theme.warn } else { theme.good }),
+                ),
+            ])];
+            lines.extend(sessions.iter().map(|session| {
+                let from = match &session.host {
+                    Some(host) => format!(" from {}", host),
+                    None => String::new(),
+                };
+                Spans::from(format!("{:<12} {:<8}{}", session.user, session.tty, from))
+            }));
+            lines
+        }
+        Some(Err(_)) => vec![Spans::from("Logged-in users: unknown")],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// How one of the `ping_hosts` answered.
+struct Ping {
+    host: String,
+    reachable: bool,
+    /// Round trip time in milliseconds, if ping reported it.
+    round_trip: Option<f64>,
+}
+
+fn pings_background(config: &Config) -> Background<Result<Vec<Ping>, String>> {
+    let hosts = config.ping_hosts.clone();
+    let interval = config.intervals.ping.unwrap_or(config.tick_rate);
+    Background::new(interval, move || ping_hosts(&hosts))
+}
+
+/// Pings every host once, all at the same time so unreachable hosts timing
+/// out don't add up.
+fn ping_hosts(hosts: &[String]) -> Result<Vec<Ping>, String> {
+    thread::scope(|scope| {
+        let pings: Vec<_> = hosts
+            .iter()
+            .map(|host| scope.spawn(move || ping(host)))
+            .collect();
+        pings
+            .into_iter()
+            .map(|ping| ping.join().expect("ping thread panicked"))
+            .collect()
+    })
+}
+
+fn ping(host: &str) -> Result<Ping, String> {
+    let output = Command::new("ping")
+        .args(["-c1", "-W1", host])
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "ping not found".to_string(),
+            _ => format!("Failed to execute ping: {}", err),
+        })?;
+
+    // 64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=11.4 ms
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let round_trip = stdout
+        .split_once("time=")
+        .and_then(|(_, rest)| rest.split_whitespace().next())
+        .and_then(|time| time.parse().ok());
+    Ok(Ping {
+        host: host.to_string(),
+        reachable: output.status.success(),
+        round_trip,
+    })
+}
+
+fn draw_pings<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    pings: &Background<Result<Vec<Ping>, String>>,
+) {
+    let block = panel_block(stale_title(title.unwrap_or("Ping"), pings), focused, theme);
+    let lines: Vec<Spans> = match pings.latest() {
+        Some(Ok(pings)) if pings.is_empty() => {
+            return draw_empty(
+                f,
+                area,
+                block,
+                theme,
+                "No hosts configured, list them in ping_hosts",
+            )
+        }
+        Some(Ok(pings)) => {
+            let width = pings.iter().map(|ping| ping.host.len()).max().unwrap_or(0);
+            pings
+                .iter()
+                .map(|ping| {
+                    let (status, color) = match (ping.reachable, ping.round_trip) {
+                        // Hosts on the LAN answer in well under a millisecond
+                        (true, Some(round_trip)) if round_trip < 1.0 => {
+                            (format!("{:.2} ms", round_trip), theme.good)
+                        }
+                        (true, Some(round_trip)) => (format!("{:.1} ms", round_trip), theme.good),
+                        (true, None) => ("reachable".to_string(), theme.good),
+                        (false, _) => ("unreachable".to_string(), theme.critical),
+                    };
+                    Spans::from(vec![
+                        Span::raw(format!("{:<width$}  ", ping.host)),
+                        Span::styled(status, Style::default().fg(color)),
+                    ])
+                })
+                .collect()
+        }
+        Some(Err(err)) => vec![Spans::from(Span::styled(
+            err.clone(),
+            Style::default().fg(theme.critical),
+        ))],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+fn hosts_background(config: &Config) -> Background<Vec<HostStatus>> {
+    let hosts = config.hosts.clone();
+    let interval = config.intervals.hosts.unwrap_or(config.tick_rate);
+    Background::new(interval, move || {
+        // All at once, like the pings, so offline hosts timing out don't add up
+        thread::scope(|scope| {
+            let statuses: Vec<_> = hosts
+                .iter()
+                .map(|host| {
+                    scope.spawn(move || HostStatus {
+                        name: host.name.clone(),
+                        stats: remote_host_stats(host),
+                    })
+                })
+                .collect();
+            hosts
+                .iter()
+                .zip(statuses)
+                .map(|(host, status)| {
+                    // One host's odd output shouldn't take the others with it
+                    status.join().unwrap_or_else(|_| HostStatus {
+                        name: host.name.clone(),
+                        stats: Err("Reading the host's output failed".to_string()),
+                    })
+                })
+                .collect()
+        })
+    })
+}
+
+fn draw_hosts<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    hosts: &Background<Vec<HostStatus>>,
+    config: &Config,
+) {
+    let thresholds = &config.thresholds;
+    let block = panel_block(
+        stale_title(title.unwrap_or("Remote Hosts"), hosts),
+        focused,
+        theme,
+    );
+    let lines: Vec<Spans> = match hosts.latest() {
+        Some(hosts) if hosts.is_empty() => {
+            return draw_empty(
+                f,
+                area,
+                block,
+                theme,
+                "No hosts configured, add them as [[hosts]] tables",
+            )
+        }
+        Some(hosts) => {
+            let width = hosts.iter().map(|host| host.name.len()).max().unwrap_or(0);
+            hosts
+                .iter()
+                .map(|host| {
+                    let name = Span::styled(
+                        format!("{:<width$}  ", host.name),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    );
+                    let stats = match &host.stats {
+                        Ok(stats) => stats,
+                        Err(err) => {
+                            return Spans::from(vec![
+                                name,
+                                Span::styled("offline", Style::default().fg(theme.critical)),
+                                Span::styled(
+                                    format!("  {}", err),
+                                    Style::default().fg(theme.muted),
+                                ),
+                            ])
+                        }
+                    };
+
+                    let percent = |used: u64, total: u64| used as f64 / total.max(1) as f64 * 100.0;
+                    let memory = percent(stats.memory_used, stats.memory_total);
+                    let disk = percent(stats.disk_used, stats.disk_total);
+                    // Colored like the load average panel, by load per core
+                    let cores = stats.cores.max(1) as f64;
+                    let load_color = if stats.load < cores {
+                        theme.good
+                    } else if stats.load < cores * 2.0 {
+                        theme.warn
+                    } else {
+                        theme.critical
+                    };
+                    Spans::from(vec![
+                        name,
+                        Span::styled("online", Style::default().fg(theme.good)),
+                        Span::raw(format!("  up {}  load ", format_uptime(stats.uptime))),
+                        Span::styled(
+                            format!("{:.2}", stats.load),
+                            Style::default().fg(load_color),
+                        ),
+                        Span::raw(format!("/{}  mem ", stats.cores)),
+                        Span::styled(
+                            format!("{:.0}%", memory),
+                            Style::default().fg(usage_color(
+                                memory,
+                                thresholds.memory_warn,
+                                thresholds.memory_critical,
+                                theme,
+                            )),
+                        ),
+                        Span::raw("  disk "),
+                        Span::styled(
+                            format!("{:.0}%", disk),
+                            Style::default().fg(usage_color(
+                                disk,
+                                thresholds.disk_warn,
+                                thresholds.disk_critical,
+                                theme,
+                            )),
+                        ),
+                    ])
+                })
+                .collect()
+        }
+        None => vec![Spans::from("Connecting…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// A listening TCP socket or bound UDP socket, as listed by `ss`.
+struct Socket {
+    protocol: String,
+    /// Address and port, e.g. `0.0.0.0:22` or `[::]:443`.
+    address: String,
+    port: u16,
+    /// Owning process name and PID. Only root gets to see these for other
+    /// users' sockets.
+    process: Option<(String, u32)>,
+}
+
+fn sockets_background(config: &Config) -> Background<Result<Vec<Socket>, String>> {
+    let ports = config.ports.clone();
+    let interval = config.intervals.ports.unwrap_or(config.tick_rate);
+    Background::new(interval, move || listening_sockets(&ports))
+}
+
+/// Runs `ss`, keeping only sockets on `ports` unless that is empty.
+fn listening_sockets(ports: &[u16]) -> Result<Vec<Socket>, String> {
+    let output = Command::new("ss")
+        .arg("-Htulpn")
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "ss not found".to_string(),
+            _ => format!("Failed to execute ss: {}", err),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ss failed ({}): {}", output.status, stderr.trim()));
+    }
+
+    // tcp LISTEN 0 128 0.0.0.0:22 0.0.0.0:* users:(("sshd",pid=812,fd=3))
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut sockets: Vec<Socket> = stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (protocol, address) = (*fields.first()?, *fields.get(4)?);
+            let port = address.rsplit_once(':')?.1.parse().ok()?;
+            let process = fields.get(6).and_then(|users| {
+                let (_, rest) = users.split_once("((\"")?;
+                let (name, rest) = rest.split_once('"')?;
+                let (_, rest) = rest.split_once("pid=")?;
+                let pid = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+                Some((name.to_string(), pid.parse().ok()?))
+            });
+            Some(Socket {
+                protocol: protocol.to_string(),
+                address: address.to_string(),
+                port,
+                process,
+            })
+        })
+        .filter(|socket| ports.is_empty() || ports.contains(&socket.port))
+        .collect();
+    sockets.sort_by(|a, b| (a.port, &a.protocol).cmp(&(b.port, &b.protocol)));
+    Ok(sockets)
+}
+
+fn draw_sockets<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    sockets: &Background<Result<Vec<Socket>, String>>,
+) {
+    let block = panel_block(
+        stale_title(title.unwrap_or("Listening Ports"), sockets),
+        focused,
+        theme,
+    );
+    let lines: Vec<Spans> = match sockets.latest() {
+        Some(Ok(sockets)) if sockets.is_empty() => {
+            return draw_empty(f, area, block, theme, "No listening ports")
+        }
+        Some(Ok(sockets)) => {
+            let width = sockets
+                .iter()
+                .map(|socket| socket.address.len())
+                .max()
+                .unwrap_or(0);
+            sockets
+                .iter()
+                .map(|socket| {
+                    let owner = match &socket.process {
+                        Some((name, pid)) => Span::raw(format!("{} ({})", name, pid)),
+                        None => Span::styled("?", Style::default().fg(theme.muted)),
+                    };
+                    Spans::from(vec![
+                        Span::raw(format!(
+                            "{:<4} {:<width$}  ",
+                            socket.protocol, socket.address
+                        )),
+                        owner,
+                    ])
+                })
+                .collect()
+        }
+        Some(Err(err)) => vec![Spans::from(Span::styled(
+            err.clone(),
+            Style::default().fg(theme.critical),
+        ))],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// A running Docker container, as listed by `docker ps`.
+struct Container {
+    name: String,
+    image: String,
+    /// Human readable, e.g. "Up 3 hours (healthy)".
+    status: String,
+}
+
+fn containers_background(config: &Config) -> Background<Result<Vec<Container>, String>> {
+    let filter = config.docker_filter.clone();
+    let interval = config.intervals.docker.unwrap_or(config.tick_rate);
+    Background::new(interval, move || running_containers(filter.as_deref()))
+}
+
+fn running_containers(name_filter: Option<&str>) -> Result<Vec<Container>, String> {
+    let mut command = Command::new("docker");
+    command.args(["ps", "--format", "{{.Names}}\t{{.Image}}\t{{.Status}}"]);
+    // Docker matches name filters as substrings already
+    if let Some(filter) = name_filter {
+        command.arg("--filter").arg(format!("name={}", filter));
+    }
+
+    let output = command.output().map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => "docker not found".to_string(),
+        _ => format!("Failed to execute docker: {}", err),
+    })?;
+    if !output.status.success() {
+        // Usually the daemon isn't running, or the socket isn't accessible
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let containers = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            Some(Container {
+                name: fields.next()?.to_string(),
+                image: fields.next()?.to_string(),
+                status: fields.next()?.to_string(),
+            })
+        })
+        .collect();
+    Ok(containers)
+}
+
+fn draw_containers<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    containers: &Background<Result<Vec<Container>, String>>,
+) {
+    let block = panel_block(
+        stale_title(title.unwrap_or("Docker Containers"), containers),
+        focused,
+        theme,
+    );
+    let lines: Vec<Spans> = match containers.latest() {
+        Some(Ok(containers)) if containers.is_empty() => {
+            return draw_empty(f, area, block, theme, "No running containers")
+        }
+        Some(Ok(containers)) => containers
+            .iter()
+            .map(|container| {
+                let color = if container.status.contains("(unhealthy)") {
+                    theme.critical
+                } else if container.status.starts_with("Up") {
+                    theme.good
+                } else {
+                    theme.warn
+                };
+                Spans::from(vec![
+                    Span::raw(format!("{}  ", container.name)),
+                    Span::styled(container.image.clone(), Style::default().fg(theme.muted)),
+                    Span::raw("  "),
+                    Span::styled(container.status.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect(),
+        Some(Err(err)) => vec![
+            Spans::from("Docker is not available"),
+            Spans::from(Span::styled(err.clone(), Style::default().fg(theme.muted))),
+        ],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+/// One GPU as reported by `nvidia-smi`. Fields the driver can't report,
+/// shown as `[N/A]` or `[Not Supported]`, are `None`.
+struct Gpu {
+    name: String,
+    utilization: Option<f64>,
+    memory_used: Option<u64>,
+    memory_total: Option<u64>,
+    temperature: Option<f32>,
+}
+
+/// Queries every NVIDIA GPU. Machines without `nvidia-smi` just have none.
+fn gpu_stats() -> Result<Vec<Gpu>, String> {
+    let output = match Command::new("nvidia-smi")
+        .arg("--query-gpu=name,utilization.gpu,memory.used,memory.total,temperature.gpu")
+        .arg("--format=csv,noheader,nounits")
+        .output()
+    {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("Failed to execute nvidia-smi: {}", err)),
+    };
+    if !output.status.success() {
+        // It prints its errors, e.g. a driver mismatch, to stdout
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let message = if stderr.trim().is_empty() {
+            stdout
+        } else {
+            stderr
+        };
+        return Err(format!(
+            "nvidia-smi failed ({}): {}",
+            output.status,
+            message.trim()
+        ));
+    }
+
+    // NVIDIA A100-SXM4-40GB, 45, 10240, 40960, 63
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let gpus = stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            // The name comes first and may itself contain commas
+            let mut fields = line.rsplitn(5, ',').map(str::trim);
+            let temperature = fields.next().and_then(|field| field.parse().ok());
+            let memory_total = fields.next().and_then(|field| field.parse().ok());
+            let memory_used = fields.next().and_then(|field| field.parse().ok());
+            let utilization = fields.next().and_then(|field| field.parse().ok());
+            let name = fields.next().unwrap_or("GPU").to_string();
+            Gpu {
+                name,
+                utilization,
+                memory_used,
+                memory_total,
+                temperature,
+            }
+        })
+        .collect();
+    Ok(gpus)
+}
+
+fn draw_gpus<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    gpus: &Background<Result<Vec<Gpu>, String>>,
+    config: &Config,
+) {
+    let unknown = || Span::styled("?", Style::default().fg(theme.muted));
+    let block = panel_block(stale_title(title.unwrap_or("GPUs"), gpus), focused, theme);
+    let lines: Vec<Spans> = match gpus.latest() {
+        Some(Ok(gpus)) if gpus.is_empty() => {
+            return draw_empty(f, area, block, theme, "No NVIDIA GPUs found")
+        }
+        Some(Ok(gpus)) => gpus
+            .iter()
+            .enumerate()
+            .map(|(index, gpu)| {
+                let mut spans = vec![Span::raw(format!("{} {}  ", index, gpu.name))];
+
+                spans.push(match gpu.utilization {
+                    Some(utilization) => Span::raw(format!("{:.0}%", utilization)),
+                    None => unknown(),
+                });
+
+                spans.push(Span::raw("  VRAM "));
+                // nvidia-smi reports memory in MiB
+                let mib = |value: u64| {
+                    format_bytes(
+                        value * 1024 * 1024,
+                        config.panel_number_format(&PanelKind::Gpu),
+                    )
+                };
+                spans.push(match (gpu.memory_used, gpu.memory_total) {
+                    (Some(used), Some(total)) => Span::styled(
+                        format!("{} / {}", mib(used), mib(total)),
+                        Style::default().fg(usage_color(
+                            percent(used, total),
+                            config.thresholds.memory_warn,
+                            config.thresholds.memory_critical,
+                            theme,
+                        )),
+                    ),
+                    (Some(used), None) => Span::raw(mib(used)),
+                    _ => unknown(),
+                });
+
+                spans.push(Span::raw("  "));
+                spans.push(match gpu.temperature {
+                    Some(temperature) => Span::styled(
+                        format!("{:.0}°C", temperature),
+                        Style::default().fg(usage_color(
+                            (temperature / DEFAULT_CRITICAL_TEMPERATURE) as f64 * 100.0,
+                            85.0,
+                            100.0,
+                            theme,
+                        )),
+                    ),
+                    None => unknown(),
+                });
+                Spans::from(spans)
+            })
+            .collect(),
+        Some(Err(err)) => vec![Spans::from(Span::styled(
+            err.clone(),
+            Style::default().fg(theme.critical),
+        ))],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+fn services_background(config: &Config) -> Background<Result<Vec<String>, String>> {
+    let units = config.programs.clone();
+    let interval = config.intervals.programs.unwrap_or(config.tick_rate);
+    Background::new(interval, move || service_states(&units))
+}
+
+/// Asks systemd for the state of each unit, e.g. "active" or "failed".
+fn service_states(units: &[String]) -> Result<Vec<String>, String> {
+    if units.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Exits non-zero whenever a unit isn't active, but still prints a state
+    // line for every unit, so only a short output means it failed
+    let output = Command::new("systemctl")
+        .arg("is-active")
+        .arg("--")
+        .args(units)
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => "systemctl not found".to_string(),
+            _ => format!("Failed to execute systemctl: {}", err),
+        })?;
+
+    let states: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect();
+    if states.len() != units.len() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "systemctl failed ({}): {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+    Ok(states)
+}
+
+fn draw_service_status<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    units: &[String],
+    services: &Background<Result<Vec<String>, String>>,
+) {
+    let block = panel_block(
+        stale_title(title.unwrap_or("Service Status"), services),
+        focused,
+        theme,
+    );
+    let lines: Vec<Spans> = match services.latest() {
+        _ if units.is_empty() => {
+            return draw_empty(
+                f,
+                area,
+                block,
+                theme,
+                "No units configured, list them in programs",
+            )
+        }
+        Some(Ok(states)) => units
+            .iter()
+            .zip(states)
+            .map(|(unit, state)| {
+                let color = match state.as_str() {
+                    "active" => theme.good,
+                    "failed" => theme.critical,
+                    "activating" | "deactivating" | "reloading" | "refreshing" => theme.warn,
+                    _ => theme.muted,
+                };
+                Spans::from(vec![
+                    Span::raw(format!("{}: ", unit)),
+                    Span::styled(state.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect(),
+        Some(Err(err)) => vec![Spans::from(Span::styled(
+            err.clone(),
+            Style::default().fg(theme.critical),
+        ))],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// The `pueue status` output for one group, or why it couldn't be fetched.
+struct PueueGroup {
+    name: String,
+    status: Result<String, String>,
+}
+
+/// Runs `pueue status` for each group, so one failing group doesn't hide the others.
+fn pueue_status(groups: &[String]) -> Vec<PueueGroup> {
+    groups
+        .iter()
+        .map(|group| PueueGroup {
+            name: group.clone(),
+            status: pueue_group_status(group),
+        })
+        .collect()
+}
+
+/// The task ID a line of `pueue status` output starts with, if it is a task row.
+fn pueue_task_id(line: &str) -> Option<usize> {
+    line.trim_start_matches(|c: char| c.is_whitespace() || c == '│')
+        .split(|c: char| c.is_whitespace() || c == '│')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn pueue_group_status(group: &str) -> Result<String, String> {
+    let output = Command::new("pueue")
+        .arg("status")
+        .arg("-g")
+        .arg(group)
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!(
+                "pueue failed ({}): {}",
+                output.status,
+                stderr.trim()
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Err("pueue not installed".to_string()),
+        Err(err) => Err(format!("Failed to execute pueue: {}", err)),
+    }
+}
+
+/// Runs `pueue restart`, `pause` or `start` for a task, returning what to
+/// tell about it in the footer.
+fn task_action(request: &TaskActionRequest) -> String {
+    let output = Command::new("pueue")
+        .arg(request.action.command())
+        .arg(request.id.to_string())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match stdout.lines().next() {
+                Some(line) if !line.trim().is_empty() => line.trim().to_string(),
+                _ => format!("{} task {}", request.action.verb(), request.id),
+            }
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            format!(
+                "pueue {} failed: {}",
+                request.action.command(),
+                stderr.trim()
+            )
+        }
+        Err(err) => format!("Failed to execute pueue: {}", err),
+    }
+}
+
+/// Lines of a task's output fetched for its log, the end being what matters.
+const TASK_LOG_LINES: usize = 10_000;
+
+/// Runs `pueue log` for one task, for the end of its output.
+fn pueue_log(id: usize) -> Result<Vec<String>, String> {
+    let output = Command::new("pueue")
+        .arg("log")
+        .arg(id.to_string())
+        .arg("--lines")
+        .arg(TASK_LOG_LINES.to_string())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let text = String::from_utf8_lossy(&output.stdout);
+            let lines: Vec<&str> = text.lines().collect();
+            // pueue adds a header to the lines asked for, and the scroll
+            // position counts lines in a u16
+            let skip = lines.len().saturating_sub(u16::MAX as usize);
+            Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!(
+                "pueue failed ({}): {}",
+                output.status,
+                stderr.trim()
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Err("pueue not installed".to_string()),
+        Err(err) => Err(format!("Failed to execute pueue: {}", err)),
+    }
+}
+
+fn draw_process_states<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    states: &ProcessStates,
+    format: NumberFormat,
+) {
+    let count = |count: usize| format_count(count as u64, format);
+    let mut totals = format!("Processes: {}", count(states.total));
+    if let Some(threads) = states.threads {
+        totals.push_str(&format!("  Threads: {}", count(threads)));
+    }
+
+    // Zombies linger when a parent never reaps its children, usually a bug
+    let zombie_color = if states.zombie > 0 {
+        theme.critical
+    } else {
+        theme.text
+    };
+    let lines = vec![
+        Spans::from(totals),
+        Spans::from(vec![
+            Span::raw(format!(
+                "Running {}  Sleeping {}  Stopped {}  ",
+                count(states.running),
+                count(states.sleeping),
+                count(states.stopped)
+            )),
+            Span::styled(
+                format!("Zombie {}", count(states.zombie)),
+                Style::default().fg(zombie_color),
+            ),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .block(panel_block(
+            title.unwrap_or("Process States"),
+            focused,
+            theme,
+        ))
+        .style(Style::default().fg(theme.text))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(paragraph, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_top_processes<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    processes: &[ProcessStats],
+    selected: usize,
+    sort: ProcessSort,
+    filter: Option<&str>,
+    units: NumberFormat,
+) {
+    // PID and the numeric columns take a fixed width, the name gets the rest
+    let name_width = (area.width as usize).saturating_sub(2 + 8 + 8 + 11).max(4);
+
+    let mut lines = vec![Spans::from(format!(
+        "{:>7} {:<name_width$} {:>7} {:>10}",
+        "PID", "NAME", "CPU%", "RSS"
+    ))];
+    for (row, process) in processes.iter().enumerate() {
+        let name: String = if process.name.chars().count() > name_width {
+            let mut truncated: String = process.name.chars().take(name_width - 1).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            process.name.clone()
+        };
+
+        let line = format!(
+            "{:>7} {:<name_width$} {:>7.1} {:>10}",
+            process.pid.as_u32(),
+            name,
+            process.cpu,
+            format_bytes(process.memory, units)
+        );
+        // The selection only matters, and so only shows, while focused
+        let style = if focused && row == selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Spans::from(Span::styled(line, style)));
+    }
+
+    let mut title = format!(
+        "{} {}",
+        title.unwrap_or("Top Processes"),
+        match sort {
+            ProcessSort::Cpu => "(by CPU, m: memory)",
+            ProcessSort::Memory => "(by memory, c: CPU)",
+        }
+    );
+    if let Some(filter) = filter.filter(|filter| !filter.is_empty()) {
+        title = format!("{} matching /{}/", title, filter);
+    }
+
+    let block = panel_block(title, focused, theme);
+    if processes.is_empty() {
+        let message = match filter {
+            Some(_) => "No process matches the filter",
+            None => "No processes",
+        };
+        return draw_empty(f, area, block, theme, message);
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_pueue_status<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    groups: &[String],
+    status: &Background<Vec<PueueGroup>>,
+    selected: usize,
+    scroll: &mut Scroll,
+    log_key: Option<String>,
+    ascii: bool,
+) {
+    let title = match (title, groups) {
+        (Some(title), _) => title.to_string(),
+        (None, [group]) => format!("Pueue {} Group", group),
+        (None, _) => "Pueue Groups".to_string(),
+    };
+
+    let mut title = stale_title(&title, status);
+    if let Some(key) = log_key.filter(|_| focused) {
+        title.push_str(&format!(" ({}: log)", key));
+    }
+    let block = panel_block(title, focused, theme);
+    if groups.is_empty() {
+        return draw_empty(
+            f,
+            area,
+            block,
+            theme,
+            "No groups configured, list them in pueue_groups",
+        );
+    }
+
+    let mut lines = Vec::new();
+    // The line of the highlighted task, to keep it scrolled into view
+    let mut selected_line = None;
+    let no_tasks = |group: &PueueGroup| {
+        group
+            .status
+            .as_ref()
+            .is_ok_and(|text| !text.lines().any(|line| pueue_task_id(line).is_some()))
+    };
+    match status.latest() {
+        Some(statuses) if statuses.iter().all(no_tasks) => {
+            return draw_empty(f, area, block, theme, "No tasks");
+        }
+        Some(statuses) => {
+            let mut task = 0;
+            for group in statuses {
+                // A single group is already named in the title
+                if statuses.len() > 1 {
+                    lines.push(Spans::from(Span::styled(
+                        group.name.clone(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )));
+                }
+                let text = match &group.status {
+                    Ok(text) => text,
+                    Err(err) => {
+                        lines.extend(err.lines().map(|line| Spans::from(line.to_string())));
+                        continue;
+                    }
+                };
+                for line in text.lines() {
+                    let mut style = Style::default();
+                    if pueue_task_id(line).is_some() {
+                        // Like the process list, only highlight while focused
+                        if focused && task == selected {
+                            style = style.add_modifier(Modifier::REVERSED);
+                            selected_line = Some(lines.len() as u16);
+                        }
+                        task += 1;
+                    }
+                    lines.push(Spans::from(Span::styled(line.to_string(), style)));
+                }
+            }
+        }
+        None => lines.push(Spans::from("Loading…")),
+    }
+
+    let inner = block.inner(area);
+    if let Some(line) = selected_line {
+        scroll.reveal(line, inner.height);
+    }
+    let offset = scroll.update(lines.len() as u16, inner.height);
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .scroll((offset, 0));
+
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, scroll, ascii);
+}
+
+/// The task log overlay, covering everything but the footer. Esc closes it,
+/// as does `close_key`, the key that opened it.
+fn draw_task_log<B: Backend>(
+    f: &mut tui::Frame<B>,
+    theme: &Theme,
+    log: &mut TaskLog,
+    close_key: Option<String>,
+    ascii: bool,
+) {
+    let size = f.size();
+    let area = Rect::new(0, 0, size.width, size.height.saturating_sub(1));
+    let close_keys = match close_key {
+        Some(key) => format!("{} or Esc", key),
+        None => "Esc".to_string(),
+    };
+    let title = format!("Log of pueue task {} ({} to close)", log.id, close_keys);
+    let block = panel_block(stale_title(&title, &log.output), true, theme);
+    let inner = block.inner(area);
+
+    // Only the visible slice becomes widgets, logs can be very long
+    let (lines, color) = match log.output.latest() {
+        Some(Ok(lines)) => {
+            let content_height = lines.len() as u16;
+            let offset = log.scroll.update(content_height, inner.height) as usize;
+            let visible: Vec<Spans> = lines
+                .iter()
+                .skip(offset)
+                .take(inner.height as usize)
+                .map(|line| Spans::from(line.as_str()))
+                .collect();
+            (visible, theme.text)
+        }
+        Some(Err(err)) => (vec![Spans::from(err.as_str())], theme.critical),
+        None => (vec![Spans::from("Loading…")], theme.muted),
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(color));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, &log.scroll, ascii);
+}
+
+/// A command panel action's output, covering everything but the footer.
+fn draw_action_output<B: Backend>(
+    f: &mut tui::Frame<B>,
+    theme: &Theme,
+    output: &mut ActionOutput,
+    ascii: bool,
+) {
+    let size = f.size();
+    let area = Rect::new(0, 0, size.width, size.height.saturating_sub(1));
+    let title = format!("Output of `{}` (Esc to close)", output.command_line);
+    let block = panel_block(stale_title(&title, &output.output), true, theme);
+    let inner = block.inner(area);
+
+    let (text, color) = match output.output.latest() {
+        Some(Ok(text)) if text.trim().is_empty() => ("Done, with no output", theme.muted),
+        Some(Ok(text)) => (text.as_str(), theme.text),
+        Some(Err(err)) => (err.as_str(), theme.critical),
+        None => ("Running…", theme.muted),
+    };
+    let content_height = text.lines().count().min(u16::MAX as usize) as u16;
+    let offset = output.scroll.update(content_height, inner.height);
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .style(Style::default().fg(color))
+        .scroll((offset, 0));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, &output.scroll, ascii);
+}
+
+fn draw_battery<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    batteries: &[Battery],
+) {
+    let block = panel_block(title.unwrap_or("Battery"), focused, theme);
+    if batteries.is_empty() {
+        return draw_empty(f, area, block, theme, "No battery");
+    }
+
+    let mut lines = Vec::new();
+    for battery in batteries {
+        let color = if battery.percent <= 10.0 {
+            theme.critical
+        } else if battery.percent <= 25.0 {
+            theme.warn
+        } else {
+            theme.good
+        };
+
+        let mut spans = vec![
+            Span::raw(format!("{}: ", battery.name)),
+            Span::styled(
+                format!("{:.0}%", battery.percent),
+                Style::default().fg(color),
+            ),
+            Span::raw(format!("  {}", battery.state)),
+        ];
+        if let Some(remaining) = battery.time_remaining {
+            let minutes = remaining.as_secs() / 60;
+            let until = if battery.state == "Charging" {
+                "until full"
+            } else {
+                "remaining"
+            };
+            spans.push(Span::raw(format!(
+                ", {}h {:02}m {}",
+                minutes / 60,
+                minutes % 60,
+                until
+            )));
+        }
+        lines.push(Spans::from(spans));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+/// Lists logged errors, newest first.
+fn draw_errors<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    errors: &ErrorLog,
+) {
+    let block = panel_block(title.unwrap_or("Errors"), focused, theme);
+    if errors.entries().is_empty() {
+        return draw_empty(f, area, block, theme, "No errors");
+    }
+
+    let lines: Vec<Spans> = errors
+        .entries()
+        .iter()
+        .rev()
+        .map(|error| {
+            let mut spans = vec![
+                Span::styled(
+                    error.last_seen.format("%H:%M:%S ").to_string(),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(
+                    format!("{}: ", error.source),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(error.message.clone(), Style::default().fg(theme.critical)),
+            ];
+            if error.count > 1 {
+                spans.push(Span::styled(
+                    format!(
+                        " (×{} since {})",
+                        error.count,
+                        error.first_seen.format("%H:%M:%S")
+                    ),
+                    Style::default().fg(theme.muted),
+                ));
+            }
+            Spans::from(spans)
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(paragraph, area);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_alerts<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    alerts: &AlertLog,
+    scroll: &mut Scroll,
+    ascii: bool,
+) {
+    let lines: Vec<Spans> = alerts
+        .alerts()
+        .iter()
+        .map(|alert| {
+            let (event, color) = match alert.level {
+                Level::Critical => ("critical", theme.critical),
+                Level::Warn => ("warning", theme.warn),
+                Level::Normal => ("recovered", theme.good),
+            };
+            Spans::from(vec![
+                Span::styled(
+                    alert.time.format("%H:%M:%S ").to_string(),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(
+                    format!("{} {}", alert.name, event),
+                    Style::default().fg(color),
+                ),
+                Span::raw(format!(" {:.0}%", alert.usage)),
+            ])
+        })
+        .collect();
+
+    let block = panel_block(title.unwrap_or("Alerts"), focused, theme);
+    if lines.is_empty() {
+        return draw_empty(f, area, block, theme, "No thresholds crossed");
+    }
+    let inner = block.inner(area);
+    // Newest alerts are at the bottom, which is where the panel starts out
+    let offset = scroll.update_following(lines.len().min(u16::MAX as usize) as u16, inner.height);
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .scroll((offset, 0));
+
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, scroll, ascii);
+}
+
+/// Runs a command panel's command, returning its output or what went wrong.
+fn command_output(command: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(command).args(args).output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).replace('\t', "    "))
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!(
+                "{} failed ({}): {}",
+                command,
+                output.status,
+                stderr.trim()
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(format!("{}: command not found", command))
+        }
+        Err(err) => Err(format!("Failed to execute {}: {}", command, err)),
+    }
+}
+
+/// Runs a command panel action. Unlike the panels themselves, what it
+/// printed is kept when it fails too, as that's where it says why.
+fn action_output(command: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(command)
+        .args(args)
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => format!("{}: command not found", command),
+            _ => format!("Failed to execute {}: {}", command, err),
+        })?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    let text = text.replace('\t', "    ");
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(format!("{} failed ({})\n{}", command, output.status, text))
+    }
+}
+
+/// How much of a log file is read at a time, working back from its end.
+const TAIL_BLOCK_SIZE: u64 = 64 * 1024;
+
+fn logs_background(config: &Config) -> Background<Result<Vec<String>, String>> {
+    let source = config.log_source.clone();
+    let lines = config.log_lines;
+    let interval = config.intervals.logs.unwrap_or(config.tick_rate);
+    Background::new(interval, move || match &source {
+        Some(LogSource::Unit(unit)) => journal_tail(unit, lines),
+        Some(LogSource::File(path)) => file_tail(path, lines)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err)),
+        None => Ok(Vec::new()),
+    })
+}
+
+fn journal_tail(unit: &str, count: usize) -> Result<Vec<String>, String> {
+    let output = Command::new("journalctl")
+        .args(["--no-pager", "--quiet", "-u", unit, "-n"])
+        .arg(count.to_string())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.replace('\t', "    "))
+            .collect()),
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!(
+                "journalctl failed ({}): {}",
+                output.status,
+                stderr.trim()
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err("journalctl not found".to_string())
+        }
+        Err(err) => Err(format!("Failed to execute journalctl: {}", err)),
+    }
+}
+
+/// The last `count` lines of a file. It is read backwards from the end a
+/// block at a time, so a huge log costs no more than its tail.
+fn file_tail(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let mut start = file.metadata()?.len();
+    let mut tail = Vec::new();
+    // One newline more than lines wanted, the first line read is likely partial
+    while start > 0 && tail.iter().filter(|&&byte| byte == b'\n').count() <= count {
+        let size = TAIL_BLOCK_SIZE.min(start);
+        start -= size;
+        let mut block = vec![0; size as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&tail);
+        tail = block;
+    }
+
+    let text = String::from_utf8_lossy(&tail);
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines[skip..]
+        .iter()
+        .map(|line| line.replace('\t', "    "))
+        .collect())
+}
+
+/// Picks out errors and warnings by the words they usually contain.
+fn log_line_color(line: &str, theme: &Theme) -> Color {
+    let line = line.to_lowercase();
+    if ["error", "fatal", "panic", "crit"]
+        .iter()
+        .any(|word| line.contains(word))
+    {
+        theme.critical
+    } else if line.contains("warn") {
+        theme.warn
+    } else {
+        theme.text
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_log_tail<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: Option<&str>,
+    source: Option<&LogSource>,
+    logs: &Background<Result<Vec<String>, String>>,
+    scroll: &mut Scroll,
+    ascii: bool,
+) {
+    let title = match (title, source) {
+        (Some(title), _) => title.to_string(),
+        (None, Some(LogSource::Unit(unit))) => format!("Log: {}", unit),
+        (None, Some(LogSource::File(path))) => format!("Log: {}", path.display()),
+        (None, None) => "Log".to_string(),
+    };
+
+    let block = panel_block(stale_title(&title, logs), focused, theme);
+    let lines: Vec<Spans> = match (source, logs.latest()) {
+        (None, _) => {
+            return draw_empty(
+                f,
+                area,
+                block,
+                theme,
+                "No log source configured, set log_source",
+            )
+        }
+        (_, Some(Ok(lines))) if lines.is_empty() => {
+            return draw_empty(f, area, block, theme, "Nothing logged yet")
+        }
+        (_, Some(Ok(lines))) => lines
+            .iter()
+            .map(|line| {
+                Spans::from(Span::styled(
+                    line.as_str(),
+                    Style::default().fg(log_line_color(line, theme)),
+                ))
+            })
+            .collect(),
+        (_, Some(Err(err))) => vec![Spans::from(Span::styled(
+            err.as_str(),
+            Style::default().fg(theme.critical),
+        ))],
+        (_, None) => vec![Spans::from("Loading…")],
+    };
+
+    let inner = block.inner(area);
+    // Newest lines are at the bottom, which is where the panel starts out
+    let offset = scroll.update_following(lines.len().min(u16::MAX as usize) as u16, inner.height);
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(theme.text))
+        .scroll((offset, 0));
+
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, scroll, ascii);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_command_panel<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    focused: bool,
+    theme: &Theme,
+    title: &str,
+    output: &Background<Result<String, String>>,
+    scroll: &mut Scroll,
+    ascii: bool,
+) {
+    let block = panel_block(stale_title(title, output), focused, theme);
+    let lines: Vec<Spans> = match output.latest() {
+        Some(Ok(output)) if output.trim().is_empty() => {
+            return draw_empty(f, area, block, theme, "No output")
+        }
+        Some(Ok(output)) => output.lines().map(Spans::from).collect(),
+        Some(Err(err)) => vec![Spans::from(Span::styled(
+            err.as_str(),
+            Style::default().fg(theme.critical),
+        ))],
+        None => vec![Spans::from("Loading…")],
+    };
+
+    let inner = block.inner(area);
+    let offset = scroll.update(lines.len() as u16, inner.height);
+
+    let paragraph = Paragraph::new(lines).block(block).scroll((offset, 0));
+
+    f.render_widget(paragraph, area);
+    draw_scrollbar(f, area, scroll, ascii);
+}
+
+/// Marks the scroll position on the right border of a panel whose content
+/// doesn't fit.
+fn draw_scrollbar<B: Backend>(f: &mut tui::Frame<B>, area: Rect, scroll: &Scroll, ascii: bool) {
+    let max_offset = scroll.max_offset();
+    if max_offset == 0 || area.height < 3 || area.width < 1 {
+        return;
+    }
+
+    let track = area.height - 2;
+    let thumb = (scroll.offset as u32 * (track as u32 - 1) / max_offset as u32) as u16;
+    let thumb_area = Rect::new(area.right() - 1, area.y + 1 + thumb, 1, 1);
+
+    f.render_widget(Paragraph::new(if ascii { "#" } else { "█" }), thumb_area);
+}
+
+/// Puts a spinner at the right end of a panel's title line while the command
+/// behind it runs, animated by how long it has been running so every redraw
+/// moves it on.
+fn draw_spinner<B: Backend>(
+    f: &mut tui::Frame<B>,
+    area: Rect,
+    theme: &Theme,
+    running: Duration,
+    ascii: bool,
+) {
+    if running < SPINNER_DELAY || area.width < MIN_PANEL_WIDTH {
+        return;
+    }
+    let frames: &[&str] = if ascii {
+        &ASCII_SPINNER_FRAMES
+    } else {
+        &SPINNER_FRAMES
+    };
+    let frame = frames[(running.as_millis() / SPINNER_FRAME.as_millis()) as usize % frames.len()];
+    // Clear of the corner, with a space either side
+    let spinner_area = Rect::new(area.right() - 4, area.y, 3, 1);
+    f.render_widget(
+        Paragraph::new(format!(" {} ", frame)).style(Style::default().fg(theme.title)),
+        spinner_area,
+    );
+}
+
+/// Appends an "updating…" marker to a panel title while its data is stale.
+fn stale_title<T: Send + 'static>(title: &str, data: &Background<T>) -> String {
+    if data.is_stale() {
+        format!("{} (updating…)", title)
+    } else {
+        title.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use format::ByteUnits;
+    use metrics::TestBackend;
+    use tui::backend::TestBackend as TerminalBackend;
+    use tui::buffer::Buffer;
+
+    /// Renders `draw` into a `width` by `height` terminal and returns what ended up on it.
+    fn render(
+        width: u16,
+        height: u16,
+        draw: impl FnOnce(&mut tui::Frame<TerminalBackend>, Rect),
+    ) -> Buffer {
+        let mut terminal = Terminal::new(TerminalBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| {
+                let area = f.size();
+                draw(f, area);
+            })
+            .unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    fn row_text(buffer: &Buffer, y: u16) -> String {
+        (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol.as_str())
+            .collect()
+    }
+
+    fn buffer_text(buffer: &Buffer) -> String {
+        (0..buffer.area.height)
+            .map(|y| row_text(buffer, y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// An app reading from `source`, as if started without arguments.
+    fn test_app(config: Config, source: TestBackend) -> App {
+        let theme = config.theme.theme();
+        App::new(
+            config,
+            theme,
+            Args::parse_from(["rashboard"]),
+            Box::new(source),
+        )
+    }
+
+    fn render_ui(app: &mut App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TerminalBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        buffer_text(terminal.backend().buffer())
+    }
+
+    /// The default config, showing only `panels`.
+    fn showing(panels: Vec<PanelKind>) -> Config {
+        Config {
+            panels,
+            ..Config::default()
+        }
+    }
+
+    /// Fails, showing the whole screen, unless each of `texts` is on it.
+    fn assert_shows(screen: &str, texts: &[&str]) {
+        for text in texts {
+            assert!(screen.contains(text), "no {:?} in\n{}", text, screen);
+        }
+    }
+
+    /// Renders a fresh app once, for tests that only look at the first frame.
+    fn render_app(config: Config, source: TestBackend, width: u16, height: u16) -> String {
+        render_ui(&mut test_app(config, source), width, height)
+    }
+
+    fn render_memory(source: &TestBackend, config: &Config) -> Buffer {
+        let theme = config.theme.theme();
+        render(60, 8, |f, area| {
+            draw_memory_disk(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                &source.memory(),
+                &source.disks(config.show_pseudo_filesystems, &|mount_point| {
+                    config.shows_mount(mount_point)
+                }),
+                &History::new(config.history_length),
+                UsageFormat::default(),
+                config,
+            )
+        })
+    }
+
+    #[test]
+    fn memory_gauge_is_critical_at_90_percent() {
+        let mut source = TestBackend::default();
+        source.memory.used = 9 * 1024 * 1024 * 1024;
+        source.memory.total = 10 * 1024 * 1024 * 1024;
+        let config = Config::default();
+
+        let buffer = render_memory(&source, &config);
+        // The filled end of the gauge, left of its centered label
+        assert_eq!(buffer.get(1, 1).bg, config.theme.theme().critical);
+        assert!(row_text(&buffer, 1).contains("(90.0%)"));
+    }
+
+    #[test]
+    fn memory_gauge_is_good_at_25_percent() {
+        let source = TestBackend::default();
+        let config = Config::default();
+
+        let buffer = render_memory(&source, &config);
+        assert_eq!(buffer.get(1, 1).bg, config.theme.theme().good);
+        assert!(row_text(&buffer, 1).contains("(25.0%)"));
+    }
+
+    #[test]
+    fn cpu_is_measuring_before_warm_up() {
+        let source = TestBackend {
+            cpu: None,
+            ..TestBackend::default()
+        };
+        let theme = Config::default().theme.theme();
+
+        let buffer = render(40, 6, |f, area| {
+            draw_cpu(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                source.cpu().as_ref(),
+                source.cpu_history(),
+                CpuCoreView::List,
+                false,
+            )
+        });
+        assert!(row_text(&buffer, 1).contains("CPU Usage: measuring..."));
+    }
+
+    #[test]
+    fn cpu_lists_each_core() {
+        let source = TestBackend::default();
+        let theme = Config::default().theme.theme();
+
+        let buffer = render(40, 6, |f, area| {
+            draw_cpu(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                source.cpu().as_ref(),
+                source.cpu_history(),
+                CpuCoreView::List,
+                false,
+            )
+        });
+        assert!(row_text(&buffer, 1).contains("CPU Usage: 12.5%"));
+        assert!(row_text(&buffer, 3).contains("cpu0"));
+        assert!(row_text(&buffer, 3).contains(" 10.0%"));
+        assert!(row_text(&buffer, 4).contains("cpu1"));
+        assert!(row_text(&buffer, 4).contains(" 15.0%"));
+    }
+
+    #[test]
+    fn ui_shows_panel_titles() {
+        let config = showing(vec![
+            PanelKind::Memory,
+            PanelKind::Cpu,
+            PanelKind::Load,
+            PanelKind::Network,
+            PanelKind::Uptime,
+        ]);
+        let screen = render_app(config, TestBackend::default(), 100, 40);
+        assert_shows(
+            &screen,
+            &[
+                "Memory and Disk Usage",
+                "CPU Usage",
+                "Load Average",
+                "Network Throughput",
+                "System Uptime",
+            ],
+        );
+    }
+
+    #[test]
+    fn ui_formats_readings() {
+        let config = showing(vec![
+            PanelKind::Memory,
+            PanelKind::Load,
+            PanelKind::Network,
+            PanelKind::Uptime,
+        ]);
+        let screen = render_app(config, TestBackend::default(), 100, 40);
+        assert_shows(
+            &screen,
+            &[
+                "Memory Usage: 4.0 GiB / 16.0 GiB (25.0%)",
+                "Available: 12.0 GiB  (cached 2.0 GiB, buffers 256.0 MiB)",
+                "Load Average: 0.50 (1m)  0.25 (5m)  0.10 (15m)",
+                "eth0",
+                "1.0 KiB/s",
+                "Uptime: 1d 1h 1m 1s",
+            ],
+        );
+    }
+
+    #[test]
+    fn ui_shows_disk_usage_with_the_default_panels() {
+        let screen = render_app(Config::default(), TestBackend::default(), 120, 40);
+        assert_shows(&screen, &["/ (ext4): 100.0 GiB / 400.0 GiB"]);
+    }
+
+    #[test]
+    fn ui_hides_pseudo_filesystems() {
+        let mut source = TestBackend::default();
+        source.disks.push(metrics::DiskStats {
+            mount_point: "/run".to_string(),
+            file_system: "tmpfs".to_string(),
+            used: 0,
+            total: 1024,
+            inodes: None,
+        });
+        let config = showing(vec![PanelKind::Memory]);
+        let screen = render_app(config, source, 100, 20);
+        assert_shows(&screen, &["/ (ext4)"]);
+        assert!(!screen.contains("/run"), "{}", screen);
+    }
+
+    #[test]
+    fn ui_reports_a_terminal_too_small() {
+        let screen = render_app(Config::default(), TestBackend::default(), 10, 2);
+        assert_shows(&screen, &["Terminal"]);
+    }
+
+    #[test]
+    fn disk_turns_critical_past_its_threshold() {
+        let mut source = TestBackend::default();
+        source.disks[0].used = source.disks[0].total / 100 * 96;
+        let config = Config::default();
+
+        let buffer = render_memory(&source, &config);
+        let text = buffer_text(&buffer);
+        assert!(text.contains("/ (ext4)"));
+        // The disk is listed below the gauge, the history, what is
+        // available and swap
+        assert_eq!(buffer.get(1, 5).fg, config.theme.theme().critical);
+    }
+
+    #[test]
+    fn zombies_are_critical() {
+        let mut source = TestBackend::default();
+        source.process_states.zombie = 1;
+        let config = showing(vec![PanelKind::ProcessStates]);
+        let theme = config.theme.theme();
+        let mut app = test_app(config, source);
+
+        let mut terminal = Terminal::new(TerminalBackend::new(80, 10)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let text = buffer_text(buffer);
+        assert!(text.contains("Processes: 120  Threads: 400"));
+
+        let row = (0..buffer.area.height)
+            .find(|&y| row_text(buffer, y).contains("Zombie 1"))
+            .unwrap();
+        let line = row_text(buffer, row);
+        // Cells are characters, not bytes, and the border is multi-byte
+        let x = line[..line.find("Zombie").unwrap()].chars().count() as u16;
+        assert_eq!(buffer.get(x, row).fg, theme.critical);
+    }
+
+    #[test]
+    fn uptime_counts_days_past_a_year() {
+        let source = TestBackend {
+            // 400 days, 23:59:59
+            uptime: 400 * 86400 + 86399,
+            ..TestBackend::default()
+        };
+        let config = showing(vec![PanelKind::Uptime]);
+        let screen = render_app(config, source, 60, 10);
+        assert_shows(&screen, &["Uptime: 400d 23h 59m 59s"]);
+        assert_shows(&screen, &["Booted: 2023-11-1"]);
+    }
+
+    #[test]
+    fn clock_uses_the_configured_time_zone_and_format() {
+        let config = Config {
+            panels: vec![PanelKind::Clock],
+            clock_timezone: Some(chrono_tz::UTC),
+            clock_format: "%Y-%m-%d".to_string(),
+            ..Config::default()
+        };
+        let screen = render_app(config, TestBackend::default(), 60, 10);
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        assert_shows(&screen, &["Clock (UTC)"]);
+        assert_shows(&screen, &[&today]);
+    }
+
+    #[test]
+    fn clock_reports_an_invalid_format() {
+        let config = Config {
+            panels: vec![PanelKind::Clock],
+            clock_format: "%Q".to_string(),
+            ..Config::default()
+        };
+        let screen = render_app(config, TestBackend::default(), 60, 10);
+        assert_shows(&screen, &["Invalid clock_format: %Q"]);
+    }
+
+    #[test]
+    fn file_tail_keeps_the_last_lines() {
+        let path = std::env::temp_dir().join(format!("rashboard-tail-{}.log", std::process::id()));
+        // Several blocks' worth, so the tail spans a block boundary
+        let text: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&path, text).unwrap();
+
+        let lines = file_tail(&path, 3).unwrap();
+        let all = file_tail(&path, 50_000).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines, ["line 19997", "line 19998", "line 19999"]);
+        assert_eq!(all.len(), 20_000);
+        assert_eq!(all[0], "line 0");
+    }
+
+    #[test]
+    fn disks_are_filtered_by_mount_point() {
+        let mut source = TestBackend::default();
+        for mount_point in ["/mnt/backup", "/mnt/scratch"] {
+            source.disks.push(metrics::DiskStats {
+                mount_point: mount_point.to_string(),
+                file_system: "xfs".to_string(),
+                used: 0,
+                total: 1024,
+                inodes: None,
+            });
+        }
+        let config: Config = toml::from_str(
+            r#"
+            panels = ["memory"]
+            disk_include = ["/mnt/*"]
+            disk_exclude = ["*/scratch"]
+            "#,
+        )
+        .unwrap();
+        let screen = render_app(config, source, 100, 20);
+        assert_shows(&screen, &["/mnt/backup (xfs)"]);
+        assert!(!screen.contains("/mnt/scratch"), "{}", screen);
+        assert!(!screen.contains("/ (ext4)"), "{}", screen);
+    }
+
+    #[test]
+    fn disks_show_their_inode_usage() {
+        let config = showing(vec![PanelKind::Memory]);
+        let screen = render_app(config, TestBackend::default(), 100, 20);
+        assert_shows(&screen, &["Inodes: 250000 / 26214400 (1%)"]);
+    }
+
+    #[test]
+    fn cpu_shows_core_frequencies() {
+        let source = TestBackend::default();
+        let theme = Config::default().theme.theme();
+
+        let buffer = render(60, 6, |f, area| {
+            draw_cpu(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                source.cpu().as_ref(),
+                source.cpu_history(),
+                CpuCoreView::List,
+                false,
+            )
+        });
+        assert!(row_text(&buffer, 3).contains("2.40 GHz"));
+        // The second core doesn't report its frequency
+        assert!(row_text(&buffer, 4)
+            .trim_end_matches(['│', ' '])
+            .ends_with('—'));
+    }
+
+    #[test]
+    fn process_filter_narrows_the_list_as_it_is_typed() {
+        let process = |pid: usize, name: &str| ProcessStats {
+            pid: Pid::from(pid),
+            name: name.to_string(),
+            cpu: 0.0,
+            memory: 0,
+        };
+        let source = TestBackend {
+            processes: vec![
+                process(1, "systemd"),
+                process(2, "Xorg"),
+                process(3, "sshd"),
+            ],
+            ..TestBackend::default()
+        };
+        let config = showing(vec![PanelKind::Processes]);
+        let mut app = test_app(config, source);
+        let names = |app: &App| -> Vec<String> {
+            app.top_processes().into_iter().map(|p| p.name).collect()
+        };
+        let press = |app: &mut App, code: KeyCode| {
+            app.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+        };
+
+        for code in [KeyCode::Char('/'), KeyCode::Char('x')] {
+            press(&mut app, code);
+        }
+        assert_eq!(names(&app), ["Xorg"]);
+        // Not a valid regex yet, so taken literally
+        press(&mut app, KeyCode::Backspace);
+        press(&mut app, KeyCode::Char('('));
+        assert!(names(&app).is_empty());
+        press(&mut app, KeyCode::Backspace);
+        for c in "d$".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(names(&app), ["systemd", "sshd"]);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(names(&app).len(), 3);
+    }
+
+    #[test]
+    fn destructive_panel_actions_wait_for_confirmation() {
+        let config: Config = toml::from_str(
+            r#"
+            panels = [{ command = "Files" }]
+
+            [[command_panels]]
+            title = "Files"
+            command = "ls"
+            actions = [
+                { key = "o", command = "ls", args = ["-l"] },
+                { key = "x", command = "rm", args = ["-rf", "build"], destructive = true },
+            ]
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        let mut app = test_app(config, TestBackend::default());
+        let press = |app: &mut App, c: char| {
+            app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        };
+
+        press(&mut app, 'x');
+        assert!(app.panel_action_requested.is_some());
+        press(&mut app, 'n');
+        assert!(app.panel_action_requested.is_none());
+        assert!(app.panel_action_output.is_none());
+
+        press(&mut app, 'o');
+        let output = app.panel_action_output.as_ref().unwrap();
+        assert_eq!(output.command_line, "ls -l");
+    }
+
+    #[test]
+    fn usage_format_cycles_between_amounts_and_percentages() {
+        let config = showing(vec![PanelKind::Memory]);
+        let mut app = test_app(config, TestBackend::default());
+        let press = |app: &mut App| {
+            app.handle_key(KeyEvent::new(KeyCode::Char('%'), KeyModifiers::NONE));
+        };
+        let memory_line = |app: &mut App| {
+            let screen = render_ui(app, 80, 20);
+            let line = screen
+                .lines()
+                .find(|line| line.contains("Memory Usage"))
+                .unwrap();
+            line.trim_matches(|c: char| c == '│' || c.is_whitespace())
+                .to_string()
+        };
+
+        assert!(memory_line(&mut app).ends_with(" GiB (25.0%)"));
+        press(&mut app);
+        assert!(memory_line(&mut app).ends_with(" GiB"));
+        press(&mut app);
+        assert_eq!(memory_line(&mut app), "Memory Usage: 25.0%");
+    }
+
+    #[test]
+    fn cpu_flags_steal_time_and_throttling() {
+        let mut source = TestBackend::default();
+        if let Some(cpu) = &mut source.cpu {
+            cpu.steal = Some(3.2);
+            cpu.throttled = Some(2);
+        }
+        let theme = Config::default().theme.theme();
+
+        let buffer = render(60, 6, |f, area| {
+            draw_cpu(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                source.cpu().as_ref(),
+                source.cpu_history(),
+                CpuCoreView::List,
+                false,
+            )
+        });
+        let row = row_text(&buffer, 1);
+        assert!(
+            row.contains("CPU Usage: 12.5%  steal 3.2%  ⚠ throttled 2×"),
+            "{}",
+            row
+        );
+        let steal = row[..row.find("steal").unwrap()].chars().count() as u16;
+        assert_eq!(buffer.get(steal, 1).fg, theme.warn);
+    }
+
+    #[test]
+    fn network_shows_totals_for_the_session_and_since_boot() {
+        let config = showing(vec![PanelKind::Network]);
+        let screen = render_app(config, TestBackend::default(), 100, 12);
+        assert_shows(
+            &screen,
+            &[": ↓ 3.0 MiB  ↑ 1.0 MiB  (since boot ↓ 5.0 GiB  ↑ 2.0 GiB)"],
+        );
+    }
+
+    #[test]
+    fn panel_titles_can_be_renamed_in_the_config() {
+        let config = Config {
+            panels: vec![PanelKind::Programs, PanelKind::Uptime],
+            panel_titles: HashMap::from([(PanelKind::Programs, "Critical Services".to_string())]),
+            ..Config::default()
+        };
+        let screen = render_app(config, TestBackend::default(), 60, 20);
+        assert_shows(&screen, &["Critical Services"]);
+        assert!(!screen.contains("Program Status"), "{}", screen);
+        assert_shows(&screen, &["System Uptime"]);
+    }
+
+    #[test]
+    fn panels_show_a_spinner_while_their_command_runs() {
+        let config = Config {
+            panels: vec![PanelKind::Command("Slow".to_string())],
+            command_panels: vec![CommandPanel {
+                title: "Slow".to_string(),
+                command: "true".to_string(),
+                args: Vec::new(),
+                interval: Duration::from_secs(10),
+                actions: Vec::new(),
+            }],
+            ..Config::default()
+        };
+        let mut app = test_app(config, TestBackend::default());
+        // Stands in for the command, running until `finish` is dropped
+        let (finish, finished) = std::sync::mpsc::channel::<()>();
+        let finished = std::sync::Mutex::new(finished);
+        app.commands[0].output = Background::new(Duration::from_secs(10), move || {
+            let _ = finished.lock().unwrap().recv();
+            Ok(String::new())
+        });
+        let has_spinner = |screen: &str| SPINNER_FRAMES.iter().any(|frame| screen.contains(frame));
+
+        app.poll_background();
+        let screen = render_ui(&mut app, 60, 10);
+        assert!(!has_spinner(&screen), "{}", screen);
+
+        app.commands[0].output.backdate(SPINNER_DELAY);
+        let screen = render_ui(&mut app, 60, 10);
+        assert!(has_spinner(&screen), "{}", screen);
+        drop(finish);
+    }
+
+    #[test]
+    fn ui_state_comes_back_as_it_was_left() {
+        let mut app = test_app(Config::default(), TestBackend::default());
+        app.focused = 1;
+        app.move_panel(1);
+        app.toggle_panel(0);
+        app.process_sort = ProcessSort::Memory;
+        app.usage_format = UsageFormat::Percent;
+        let state = app.ui_state();
+        assert_eq!(
+            state.panels[..3],
+            [PanelKind::Memory, PanelKind::Load, PanelKind::Cpu]
+        );
+
+        let mut app = test_app(Config::default(), TestBackend::default());
+        app.restore_ui_state(state.clone());
+        assert_eq!(app.ui_state(), state);
+        assert_eq!(app.focused, 2);
+        assert_eq!(app.hidden, HashSet::from([0]));
+
+        // Other panels in the config than were left keep the configured order
+        let config = showing(vec![PanelKind::Cpu, PanelKind::Memory]);
+        let mut app = test_app(config, TestBackend::default());
+        app.restore_ui_state(state);
+        assert_eq!(app.config.panels, [PanelKind::Cpu, PanelKind::Memory]);
+        assert_eq!(app.hidden, HashSet::from([1]));
+        assert_eq!(app.focused, 0);
+    }
+
+    #[test]
+    fn cpu_cores_can_be_shown_as_bars() {
+        let mut source = TestBackend::default();
+        if let Some(cpu) = &mut source.cpu {
+            cpu.cores = (0..12).map(|core| core as f32 * 9.0).collect();
+        }
+        let theme = Config::default().theme.theme();
+
+        let buffer = render(30, 13, |f, area| {
+            draw_cpu(
+                f,
+                area,
+                false,
+                &theme,
+                None,
+                source.cpu().as_ref(),
+                source.cpu_history(),
+                CpuCoreView::Bars,
+                false,
+            )
+        });
+        let screen = buffer_text(&buffer);
+
+        // Seven bars fit across, the rest wrap onto a second row
+        let rows: Vec<usize> = ["0   1   2   3   4   5   6", "7   8   9   10  11"]
+            .iter()
+            .filter_map(|labels| screen.lines().position(|line| line.contains(labels)))
+            .collect();
+        assert_eq!(rows.len(), 2, "{}", screen);
+        assert!(rows[0] < rows[1], "{}", screen);
+
+        // The busiest core is colored as critical
+        let critical = buffer
+            .content
+            .iter()
+            .filter(|cell| cell.fg == theme.critical)
+            .count();
+        assert!(critical > 0, "{}", screen);
+    }
+
+    #[test]
+    fn gauges_blend_from_green_to_red_with_a_gradient() {
+        let theme = Theme {
+            gradient: true,
+            ..Config::default().theme.theme()
+        };
+        assert_eq!(
+            gauge_color(0.0, 75.0, 90.0, &theme),
+            Color::Rgb(0x4c, 0xaf, 0x50)
+        );
+        assert_eq!(
+            gauge_color(75.0, 75.0, 90.0, &theme),
+            Color::Rgb(0xff, 0xc1, 0x07)
+        );
+        assert_eq!(
+            gauge_color(95.0, 75.0, 90.0, &theme),
+            Color::Rgb(0xf4, 0x43, 0x36)
+        );
+        // Half way to the warning level is half way to yellow
+        assert_eq!(
+            gauge_color(37.5, 75.0, 90.0, &theme),
+            Color::Rgb(0xa6, 0xb8, 0x2c)
+        );
+
+        // Without, the three steps of the theme
+        let theme = Theme {
+            gradient: false,
+            ..theme
+        };
+        assert_eq!(gauge_color(37.5, 75.0, 90.0, &theme), theme.good);
+    }
+
+    #[test]
+    fn health_reports_the_worst_reading() {
+        let config = showing(vec![PanelKind::Health]);
+        let mut app = test_app(config, TestBackend::default());
+        let screen = render_ui(&mut app, 60, 10);
+        assert_shows(&screen, &["OK  all within their thresholds"]);
+
+        // Memory is only read while something shows it, like this panel
+        let mut source = TestBackend::default();
+        let mut refreshed = source.memory.clone();
+        refreshed.used = refreshed.total / 100 * 96;
+        source.refreshed_memory = Some(refreshed);
+        if let Some(disk) = source.disks.first_mut() {
+            disk.used = disk.total / 100 * 80;
+        }
+        let config = showing(vec![PanelKind::Health]);
+        let mut app = test_app(config, source);
+        app.refresh();
+        let screen = render_ui(&mut app, 60, 10);
+        assert_shows(&screen, &["CRITICAL  memory at 96% (+1 more)"]);
+    }
+
+    #[test]
+    fn severity_markers_spell_out_the_level() {
+        let mut source = TestBackend::default();
+        source.memory.used = source.memory.total / 100 * 95;
+        let config = Config {
+            theme: ThemeName::ColorBlind,
+            severity_markers: true,
+            ..Config::default()
+        };
+
+        let buffer = render_memory(&source, &config);
+        assert!(row_text(&buffer, 1).contains("(95.0%) !!"));
+        assert_eq!(buffer.get(1, 1).bg, ThemeName::ColorBlind.theme().critical);
+        let screen = buffer_text(&buffer);
+        assert_shows(&screen, &[" OK"]);
+    }
+
+    #[test]
+    fn byte_units_can_differ_per_panel_and_for_disks() {
+        let source = TestBackend::default();
+        let config: Config = toml::from_str(
+            r#"
+            panel_byte_units = { network = "decimal" }
+            disk_byte_units = "decimal"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.panel_number_format(&PanelKind::Network).byte_units,
+            ByteUnits::Decimal
+        );
+        assert_eq!(
+            config.panel_number_format(&PanelKind::Memory).byte_units,
+            ByteUnits::Binary
+        );
+
+        let screen = buffer_text(&render_memory(&source, &config));
+        assert_shows(&screen, &["GiB"]);
+        assert_shows(&screen, &["GB /"]);
+    }
+
+    #[test]
+    fn memory_pressure_is_only_reported_when_it_keeps_rising() {
+        let unavailable = [84, 85, 86, 86, 87, 88, 89, 90, 91, 92];
+        let swap = [30, 30, 31, 32, 33, 34, 35, 36, 37, 38];
+        assert!(memory_pressure_rising(&unavailable, &swap));
+
+        // Swap staying put, a dip on the way, too little history
+        assert!(!memory_pressure_rising(&unavailable, &[30; 10]));
+        let dip = [84, 85, 86, 80, 87, 88, 89, 90, 91, 92];
+        assert!(!memory_pressure_rising(&dip, &swap));
+        assert!(!memory_pressure_rising(&unavailable[5..], &swap[5..]));
+        // Plenty left, however fast it goes
+        let low = [40, 45, 50, 55, 60, 65, 70, 75, 80, 85];
+        assert!(!memory_pressure_rising(&low, &swap));
+
+        let mut source = TestBackend::default();
+        source.memory.swap_total = 100;
+        let config = showing(vec![PanelKind::MemoryPressure]);
+        let mut app = test_app(config, source);
+        for (&unavailable, &swap) in unavailable.iter().zip(&swap) {
+            app.pressure_history.0.push(unavailable);
+            app.pressure_history.1.push(swap);
+        }
+        let screen = render_ui(&mut app, 80, 10);
+        assert_shows(
+            &screen,
+            &["Unavailable: 92%  Swap: 38%  ⚠ memory pressure rising"],
+        );
+    }
+
+    #[test]
+    fn empty_panels_say_so_in_the_middle() {
+        let theme = ThemeName::Dark.theme();
+        let buffer = render(30, 7, |f, area| {
+            draw_errors(f, area, false, &theme, None, &ErrorLog::default())
+        });
+        assert_eq!(row_text(&buffer, 3), "│          No errors         │");
+        assert_eq!(buffer.get(11, 3).fg, theme.muted);
+
+        let buffer = render(30, 7, |f, area| {
+            draw_temperatures(f, area, false, &theme, None, &[], false)
+        });
+        assert!(row_text(&buffer, 3).contains("No sensors available"));
+    }
+
+    #[test]
+    fn notifications_keep_memory_refreshed_without_its_panel() {
+        let mut source = TestBackend::default();
+        let mut refreshed = source.memory.clone();
+        refreshed.used += 1024;
+        source.refreshed_memory = Some(refreshed.clone());
+        let config = Config {
+            panels: vec![PanelKind::Uptime],
+            notifications: true,
+            ..Config::default()
+        };
+        let mut app = test_app(config, source);
+        app.refresh();
+        assert_eq!(app.source.memory().used, refreshed.used);
+    }
+
+    #[test]
+    fn pueue_task_actions_run_once_confirmed() {
+        let config = showing(vec![PanelKind::Pueue]);
+        let mut app = test_app(config, TestBackend::default());
+        app.pueue = Background::new(Duration::MAX, Vec::new);
+        let press =
+            |app: &mut App, c| app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+        app.task_action_requested = Some(TaskActionRequest {
+            action: TaskAction::Restart,
+            id: 3,
+        });
+        press(&mut app, 'n');
+        assert_eq!(app.status(), Some("Restart cancelled"));
+        assert!(app.task_action.is_none());
+
+        app.task_action_requested = Some(TaskActionRequest {
+            action: TaskAction::Restart,
+            id: 3,
+        });
+        press(&mut app, 'y');
+        assert_eq!(app.status(), Some("Asking pueue to restart task 3…"));
+        assert!(app.task_action.is_some());
+        // Stands in for pueue, before the first poll would have started it
+        app.task_action = Some(Background::new(Duration::MAX, || {
+            "Restarted task 3".to_string()
+        }));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.task_action.is_some() && Instant::now() < deadline {
+            app.poll_background();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(app.task_action.is_none());
+        assert_eq!(app.status(), Some("Restarted task 3"));
+    }
+
+    #[test]
+    fn network_chart_is_drawn_in_ascii_when_asked() {
+        let config = Config {
+            panels: vec![PanelKind::Network],
+            ascii: true,
+            ..Config::default()
+        };
+        let mut app = test_app(config, TestBackend::default());
+        for rate in [1024, 4096, 2048] {
+            app.network_history.0.push(rate);
+            app.network_history.1.push(rate / 2);
+        }
+        let screen = render_ui(&mut app, 60, 16);
+        assert_shows(&screen, &["rx"]);
+        assert!(!screen.contains("↓ rx"), "{}", screen);
+        assert!(
+            !screen
+                .chars()
+                .any(|c| ('\u{2800}'..='\u{28ff}').contains(&c)),
+            "{}",
+            screen
+        );
+    }
+
+    #[test]
+    fn task_log_hints_name_the_bound_key() {
+        let config: Config = toml::from_str(
+            r#"
+            panels = ["pueue"]
+            pueue_groups = ["default"]
+            keys = { o = "show_task_log", L = "none" }
+            "#,
+        )
+        .unwrap();
+        let mut app = test_app(config, TestBackend::default());
+
+        assert_shows(&render_ui(&mut app, 80, 12), &["(o: log)"]);
+        app.task_log = Some(TaskLog::new(3, Duration::MAX));
+        assert_shows(
+            &render_ui(&mut app, 80, 12),
+            &["Log of pueue task 3 (o or Esc to close)"],
+        );
+
+        // With no key for it, only Esc closes the log
+        app.bindings = keys::bindings(&toml::from_str(r#"keys = { L = "none" }"#).unwrap());
+        let screen = render_ui(&mut app, 80, 12);
+        assert_shows(&screen, &["Log of pueue task 3 (Esc to close)"]);
+    }
+
+    #[test]
+    fn once_waits_for_the_background_readings() {
+        let mut source = TestBackend::default();
+        let mut refreshed = source.memory.clone();
+        refreshed.used = refreshed.total / 4 * 3;
+        source.refreshed_memory = Some(refreshed);
+        let config = Config {
+            panels: vec![PanelKind::Uptime],
+            programs: vec!["nginx".to_string()],
+            program_check: ProgramCheck::Systemd,
+            ..Config::default()
+        };
+        let theme = config.theme.theme();
+        let args = Args::parse_from(["rashboard", "--once"]);
+        let mut app = App::new(config, theme, args, Box::new(source));
+        // Slower than a first poll, like systemctl and the package manager
+        app.services = Background::new(Duration::MAX, || {
+            thread::sleep(Duration::from_millis(100));
+            Ok(vec!["active".to_string()])
+        });
+        app.package_manager = Some(PackageManager::Apt);
+        app.updates = Background::new(Duration::MAX, || {
+            thread::sleep(Duration::from_millis(100));
+            Ok(vec![PackageUpdate {
+                name: "bash".to_string(),
+                from: None,
+                to: "5.2".to_string(),
+            }])
+        });
+
+        let summary = app.once_summary(Duration::from_secs(10));
+        // Memory is read without its panel shown
+        assert!(summary.contains("(75.0%)"), "{}", summary);
+        assert!(summary.contains("Updates: 1 available"), "{}", summary);
+        assert!(summary.contains("Program: nginx active"), "{}", summary);
+    }
+
+    #[test]
+    fn commands_only_run_for_the_panels_shown() {
+        let config = showing(vec![PanelKind::Uptime]);
+        let mut app = test_app(config, TestBackend::default());
+        // What hosts without pueue or a reachable package mirror answer
+        app.pueue = Background::new(Duration::MAX, || {
+            vec![PueueGroup {
+                name: "default".to_string(),
+                status: Err("pueue not found".to_string()),
+            }]
+        });
+        app.package_manager = Some(PackageManager::Apt);
+        app.updates = Background::new(Duration::MAX, || Err("apt failed".to_string()));
+
+        app.poll_background();
+        assert!(app.pueue.running_for().is_none());
+        assert!(app.updates.running_for().is_none());
+        app.poll_background();
+        assert!(app.errors.is_empty());
+    }
 }
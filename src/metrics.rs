@@ -0,0 +1,1043 @@
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::Instant;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{
+    Components, Disks, Networks, Pid, ProcessStatus, Signal, System, MINIMUM_CPU_UPDATE_INTERVAL,
+};
+
+use crate::config::MIN_TICK_RATE;
+use crate::history::History;
+
+/// Filesystems that don't correspond to real storage and are hidden by default.
+pub const PSEUDO_FILESYSTEMS: &[&str] =
+    &["tmpfs", "devtmpfs", "overlay", "squashfs", "proc", "sysfs"];
+
+/// Memory and swap, in bytes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryStats {
+    pub used: u64,
+    pub total: u64,
+    /// What can be handed out without swapping, reclaimable caches included.
+    pub available: u64,
+    /// Page cache and buffers, which count as used but are given back under
+    /// pressure. Only Linux reports these.
+    pub cached: Option<u64>,
+    pub buffers: Option<u64>,
+    pub swap_used: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+pub fn collect_memory(sys: &System) -> MemoryStats {
+    let (cached, buffers) = page_cache();
+    MemoryStats {
+        used: sys.used_memory(),
+        total: sys.total_memory(),
+        available: sys.available_memory(),
+        cached,
+        buffers,
+        swap_used: sys.used_swap(),
+        swap_total: sys.total_swap(),
+        swap_free: sys.free_swap(),
+    }
+}
+
+/// The `Cached` and `Buffers` sizes from `/proc/meminfo`, in bytes.
+#[cfg(target_os = "linux")]
+fn page_cache() -> (Option<u64>, Option<u64>) {
+    let Ok(meminfo) = std::fs::read_to_string("/proc/meminfo") else {
+        return (None, None);
+    };
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let kib = line.strip_prefix(name)?.strip_prefix(':')?;
+            let kib: u64 = kib.trim().strip_suffix("kB")?.trim_end().parse().ok()?;
+            Some(kib * 1024)
+        })
+    };
+    (field("Cached"), field("Buffers"))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn page_cache() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// Space on one mounted filesystem, in bytes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiskStats {
+    pub mount_point: String,
+    pub file_system: String,
+    pub used: u64,
+    pub total: u64,
+    /// Absent where the platform or the filesystem has no fixed number of
+    /// inodes, such as btrfs.
+    pub inodes: Option<InodeStats>,
+}
+
+/// Inodes on one filesystem, which can run out while there is space left.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InodeStats {
+    pub used: u64,
+    pub total: u64,
+}
+
+pub fn collect_disks(
+    disks: &Disks,
+    show_pseudo_filesystems: bool,
+    shows_mount: &dyn Fn(&str) -> bool,
+) -> Vec<DiskStats> {
+    let mut devices = HashSet::new();
+    disks
+        .list()
+        .iter()
+        .filter_map(|disk| {
+            let file_system = disk.file_system().to_string_lossy().into_owned();
+            if !show_pseudo_filesystems && PSEUDO_FILESYSTEMS.contains(&file_system.as_str()) {
+                return None;
+            }
+            // Filtered before the duplicates below, so a device whose first
+            // mount point is left out still shows under one that isn't
+            let mount_point = disk.mount_point().display().to_string();
+            if !shows_mount(&mount_point) {
+                return None;
+            }
+            // Bind mounts and the like list a device again under another
+            // mount point, only the first one shown is kept. Pseudo
+            // filesystems are named after their type rather than a device,
+            // so each of those counts on its own.
+            let device = disk.name().to_string_lossy();
+            if device.starts_with('/') && !devices.insert(device.into_owned()) {
+                return None;
+            }
+
+            let total = disk.total_space();
+            Some(DiskStats {
+                mount_point,
+                file_system,
+                used: total.saturating_sub(disk.available_space()),
+                total,
+                inodes: collect_inodes(disk.mount_point()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn collect_inodes(mount_point: &Path) -> Option<InodeStats> {
+    let stats = nix::sys::statvfs::statvfs(mount_point).ok()?;
+    let total = stats.files() as u64;
+    if total == 0 {
+        return None;
+    }
+    Some(InodeStats {
+        used: total.saturating_sub(stats.files_free() as u64),
+        total,
+    })
+}
+
+#[cfg(not(unix))]
+fn collect_inodes(_mount_point: &Path) -> Option<InodeStats> {
+    None
+}
+
+/// CPU usage in percent, overall and per core, and the cores' clock speeds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CpuStats {
+    pub usage: f32,
+    pub cores: Vec<f32>,
+    /// Current clock speed per core in MHz, 0 where it can't be read.
+    pub frequencies: Vec<u64>,
+    /// Percent of the time since the last sample that the hypervisor ran
+    /// something else while this VM wanted the CPU. Linux only.
+    pub steal: Option<f32>,
+    /// Times the cores were slowed down for running too hot since the
+    /// dashboard started, where the CPU counts them.
+    pub throttled: Option<u64>,
+}
+
+pub fn collect_cpu(sys: &System, sampler: &CpuSampler) -> CpuStats {
+    CpuStats {
+        usage: sys.global_cpu_usage(),
+        cores: sys.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+        frequencies: sys.cpus().iter().map(|cpu| cpu.frequency()).collect(),
+        steal: sampler.steal,
+        throttled: sampler.throttled(),
+    }
+}
+
+/// Jiffies spent stolen and in total across all CPUs, from the first line
+/// of `/proc/stat`.
+#[derive(Clone, Copy)]
+struct CpuTimes {
+    steal: u64,
+    total: u64,
+}
+
+fn read_cpu_times() -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    // cpu user nice system idle iowait irq softirq steal guest guest_nice
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    // Guest time is already counted in user time
+    Some(CpuTimes {
+        steal: *times.get(7)?,
+        total: times.iter().take(8).sum(),
+    })
+}
+
+/// The thermal throttling events counted by all cores since boot, where
+/// the CPU exposes them, as Intel ones do.
+fn read_throttle_count() -> Option<u64> {
+    let mut total = None;
+    for entry in std::fs::read_dir("/sys/devices/system/cpu").ok()?.flatten() {
+        let name = entry.file_name();
+        let is_core = name
+            .to_str()
+            .and_then(|name| name.strip_prefix("cpu"))
+            .is_some_and(|number| number.parse::<u32>().is_ok());
+        if !is_core {
+            continue;
+        }
+        let path = entry.path().join("thermal_throttle/core_throttle_count");
+        if let Some(count) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|count| count.trim().parse::<u64>().ok())
+        {
+            *total.get_or_insert(0) += count;
+        }
+    }
+    total
+}
+
+/// Tracks CPU sampling, since sysinfo can only compute usage from two
+/// refreshes spaced at least `MINIMUM_CPU_UPDATE_INTERVAL` apart.
+pub struct CpuSampler {
+    last_refresh: Option<Instant>,
+    pub warmed_up: bool,
+    /// Overall usage in percent, one sample per refresh once warmed up.
+    pub history: History,
+    times: Option<CpuTimes>,
+    steal: Option<f32>,
+    /// The throttling count at the first and the latest sample.
+    throttle_counts: Option<(u64, u64)>,
+}
+
+impl CpuSampler {
+    pub fn new(history_length: usize) -> Self {
+        CpuSampler {
+            last_refresh: None,
+            warmed_up: false,
+            history: History::new(history_length),
+            times: None,
+            steal: None,
+            throttle_counts: None,
+        }
+    }
+
+    fn throttled(&self) -> Option<u64> {
+        self.throttle_counts
+            .map(|(first, latest)| latest.saturating_sub(first))
+    }
+
+    pub fn refresh(&mut self, sys: &mut System) {
+        match self.last_refresh {
+            // Too soon after the previous sample, keep the old values
+            Some(last) if last.elapsed() < MINIMUM_CPU_UPDATE_INTERVAL => return,
+            Some(_) => self.warmed_up = true,
+            None => {}
+        }
+
+        sys.refresh_cpu_all();
+        self.last_refresh = Some(Instant::now());
+        if self.warmed_up {
+            self.history.push(sys.global_cpu_usage().round() as u64);
+        }
+
+        let times = read_cpu_times();
+        if let (Some(previous), Some(times)) = (self.times, times) {
+            let total = times.total.saturating_sub(previous.total);
+            if total > 0 {
+                let steal = times.steal.saturating_sub(previous.steal);
+                self.steal = Some(steal as f32 / total as f32 * 100.0);
+            }
+        }
+        self.times = times;
+        if let Some(count) = read_throttle_count() {
+            let first = self.throttle_counts.map_or(count, |(first, _)| first);
+            self.throttle_counts = Some((first, count));
+        }
+    }
+}
+
+/// The 1, 5 and 15 minute load averages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadStats {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+/// The load average, or `None` where the OS has none.
+pub fn collect_load() -> Option<LoadStats> {
+    // sysinfo reports zeros where the OS has no load average
+    if cfg!(windows) {
+        return None;
+    }
+    let load = System::load_average();
+    Some(LoadStats {
+        one: load.one,
+        five: load.five,
+        fifteen: load.fifteen,
+    })
+}
+
+pub fn collect_uptime() -> u64 {
+    System::uptime()
+}
+
+/// When the system booted, in seconds since the Unix epoch.
+pub fn collect_boot_time() -> u64 {
+    System::boot_time()
+}
+
+/// One sensor's reading, in degrees Celsius.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemperatureStats {
+    pub label: String,
+    pub temperature: f32,
+    /// Where the sensor says things get dangerous, if it says.
+    pub critical: Option<f32>,
+}
+
+/// Sensors with a usable reading, skipping ones that report garbage.
+pub fn collect_temperatures(components: &Components) -> Vec<TemperatureStats> {
+    components
+        .iter()
+        .filter_map(|component| {
+            let temperature = component.temperature().filter(|t| t.is_finite())?;
+            Some(TemperatureStats {
+                label: component.label().to_string(),
+                temperature,
+                critical: component.critical().filter(|critical| *critical > 0.0),
+            })
+        })
+        .collect()
+}
+
+/// The processes running under one watched program name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgramStats {
+    pub name: String,
+    pub processes: usize,
+    /// The lowest PID, usually the parent, if any are running.
+    pub first_pid: Option<Pid>,
+    /// Summed over all of the program's processes.
+    pub cpu: f32,
+    pub memory: u64,
+}
+
+pub fn collect_programs(sys: &System, programs: &[String]) -> Vec<ProgramStats> {
+    programs
+        .iter()
+        .map(|program| {
+            // Threads carry their process's name, don't count them twice
+            let processes: Vec<_> = sys
+                .processes_by_exact_name(OsStr::new(program))
+                .filter(|process| process.thread_kind().is_none())
+                .collect();
+            ProgramStats {
+                name: program.clone(),
+                processes: processes.len(),
+                first_pid: processes.iter().map(|process| process.pid()).min(),
+                cpu: processes.iter().map(|process| process.cpu_usage()).sum(),
+                memory: processes.iter().map(|process| process.memory()).sum(),
+            }
+        })
+        .collect()
+}
+
+/// Ordering of the top processes panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSort {
+    Cpu,
+    Memory,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessStats {
+    pub pid: Pid,
+    pub name: String,
+    pub cpu: f32,
+    pub memory: u64,
+}
+
+/// The `count` busiest processes by `sort`, busiest first, of those whose
+/// name matches `filter` if there is one.
+pub fn collect_top_processes(
+    sys: &System,
+    sort: ProcessSort,
+    count: usize,
+    filter: Option<&Regex>,
+) -> Vec<ProcessStats> {
+    // Linux lists threads as tasks too, only keep the processes themselves
+    let mut processes: Vec<_> = sys
+        .processes()
+        .values()
+        .filter(|process| process.thread_kind().is_none())
+        .filter(|process| {
+            filter.is_none_or(|filter| filter.is_match(&process.name().to_string_lossy()))
+        })
+        .collect();
+    match sort {
+        ProcessSort::Cpu => processes.sort_by(|a, b| b.cpu_usage().total_cmp(&a.cpu_usage())),
+        ProcessSort::Memory => processes.sort_by_key(|process| std::cmp::Reverse(process.memory())),
+    }
+    processes
+        .into_iter()
+        .take(count)
+        .map(|process| ProcessStats {
+            pid: process.pid(),
+            name: process.name().to_string_lossy().into_owned(),
+            cpu: process.cpu_usage(),
+            memory: process.memory(),
+        })
+        .collect()
+}
+
+/// How many processes are in each state.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessStates {
+    pub total: usize,
+    pub running: usize,
+    /// Including idle kernel threads and uninterruptible disk sleep.
+    pub sleeping: usize,
+    /// Stopped by a signal or by a tracer.
+    pub stopped: usize,
+    pub zombie: usize,
+    /// Processes and their threads together, where the OS lists threads.
+    pub threads: Option<usize>,
+}
+
+pub fn collect_process_states(sys: &System) -> ProcessStates {
+    let mut states = ProcessStates::default();
+    let mut tasks = 0;
+    for process in sys.processes().values() {
+        tasks += 1;
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        states.total += 1;
+        match process.status() {
+            ProcessStatus::Run => states.running += 1,
+            ProcessStatus::Sleep
+            | ProcessStatus::Idle
+            | ProcessStatus::UninterruptibleDiskSleep => states.sleeping += 1,
+            ProcessStatus::Stop | ProcessStatus::Tracing => states.stopped += 1,
+            ProcessStatus::Zombie => states.zombie += 1,
+            _ => {}
+        }
+    }
+    // Only Linux lists threads alongside processes
+    if cfg!(target_os = "linux") {
+        states.threads = Some(tasks);
+    }
+    states
+}
+
+/// Facts about the host that don't change while the dashboard runs.
+pub struct SystemInfo {
+    pub hostname: Option<String>,
+    /// Distribution name and version, e.g. "Debian GNU/Linux 12".
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub arch: String,
+}
+
+pub fn collect_system_info() -> SystemInfo {
+    let os = match (System::name(), System::os_version()) {
+        (Some(name), Some(version)) => Some(format!("{} {}", name, version)),
+        (name, version) => name.or(version),
+    };
+    SystemInfo {
+        hostname: System::host_name(),
+        os,
+        kernel: System::kernel_version(),
+        arch: System::cpu_arch(),
+    }
+}
+
+/// Throughput of a single network interface, in bytes per second, and the
+/// bytes it moved in total.
+pub struct InterfaceRate {
+    pub name: String,
+    pub received: f64,
+    pub transmitted: f64,
+    /// Since the dashboard started or the totals were last reset.
+    pub session_received: u64,
+    pub session_transmitted: u64,
+    /// Since boot, or since the interface came up.
+    pub total_received: u64,
+    pub total_transmitted: u64,
+}
+
+/// Tracks network counters and turns the deltas between refreshes into rates.
+pub struct NetworkSampler {
+    networks: Networks,
+    last_refresh: Instant,
+    pub rates: Vec<InterfaceRate>,
+    /// Bytes received and transmitted per interface this session.
+    session: HashMap<String, (u64, u64)>,
+}
+
+impl NetworkSampler {
+    pub fn new() -> Self {
+        let networks = Networks::new_with_refreshed_list();
+
+        // No delta exists yet, so every interface starts out idle
+        let mut rates: Vec<InterfaceRate> = networks
+            .iter()
+            .map(|(name, data)| InterfaceRate {
+                name: name.clone(),
+                received: 0.0,
+                transmitted: 0.0,
+                session_received: 0,
+                session_transmitted: 0,
+                total_received: data.total_received(),
+                total_transmitted: data.total_transmitted(),
+            })
+            .collect();
+        rates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        NetworkSampler {
+            networks,
+            last_refresh: Instant::now(),
+            rates,
+            session: HashMap::new(),
+        }
+    }
+
+    /// Starts counting the session totals from zero again.
+    pub fn reset_totals(&mut self) {
+        self.session.clear();
+        for rate in &mut self.rates {
+            rate.session_received = 0;
+            rate.session_transmitted = 0;
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let elapsed = self.last_refresh.elapsed();
+        if elapsed < MIN_TICK_RATE {
+            return;
+        }
+
+        // Refreshing the list picks up interfaces that came or went, new
+        // ones start from their current counters so they report 0 at first
+        self.networks.refresh(true);
+        self.last_refresh = Instant::now();
+
+        let seconds = elapsed.as_secs_f64();
+        let session = &mut self.session;
+        self.rates = self
+            .networks
+            .iter()
+            .map(|(name, data)| {
+                let totals = session.entry(name.clone()).or_default();
+                totals.0 += data.received();
+                totals.1 += data.transmitted();
+                InterfaceRate {
+                    name: name.clone(),
+                    received: data.received() as f64 / seconds,
+                    transmitted: data.transmitted() as f64 / seconds,
+                    session_received: totals.0,
+                    session_transmitted: totals.1,
+                    total_received: data.total_received(),
+                    total_transmitted: data.total_transmitted(),
+                }
+            })
+            .collect();
+        self.rates.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+/// Read and write throughput of a single disk, in bytes per second.
+pub struct DiskRate {
+    pub name: String,
+    pub read: f64,
+    pub written: f64,
+}
+
+/// Tracks cumulative disk I/O counters and turns the deltas between
+/// refreshes into rates, like `NetworkSampler` does for interfaces.
+pub struct DiskIoSampler {
+    /// Total bytes read and written per disk at the last refresh.
+    totals: HashMap<String, (u64, u64)>,
+    last_refresh: Instant,
+    pub rates: Vec<DiskRate>,
+}
+
+impl DiskIoSampler {
+    pub fn new(disks: &Disks) -> Self {
+        let mut sampler = DiskIoSampler {
+            totals: HashMap::new(),
+            last_refresh: Instant::now(),
+            rates: Vec::new(),
+        };
+        sampler.refresh(disks);
+        sampler
+    }
+
+    /// Reads the counters of freshly refreshed `disks`.
+    pub fn refresh(&mut self, disks: &Disks) {
+        let seconds = self.last_refresh.elapsed().as_secs_f64().max(f64::EPSILON);
+        self.last_refresh = Instant::now();
+
+        let mut totals = HashMap::new();
+        self.rates.clear();
+        for disk in disks.list() {
+            let fs = disk.file_system().to_string_lossy();
+            if PSEUDO_FILESYSTEMS.contains(&fs.as_ref()) {
+                continue;
+            }
+            // A device mounted in several places is only counted once
+            let name = disk.name().to_string_lossy().into_owned();
+            if totals.contains_key(&name) {
+                continue;
+            }
+
+            let usage = disk.usage();
+            let current = (usage.total_read_bytes, usage.total_written_bytes);
+            // A disk seen for the first time has no baseline yet, report it
+            // idle rather than its whole lifetime of I/O as one tick's worth
+            let (read, written) = match self.totals.get(&name) {
+                Some(&(read, written)) => (
+                    current.0.saturating_sub(read) as f64 / seconds,
+                    current.1.saturating_sub(written) as f64 / seconds,
+                ),
+                None => (0.0, 0.0),
+            };
+
+            self.rates.push(DiskRate {
+                name: name.clone(),
+                read,
+                written,
+            });
+            totals.insert(name, current);
+        }
+
+        self.rates.sort_by(|a, b| a.name.cmp(&b.name));
+        self.totals = totals;
+    }
+}
+
+/// What came of asking a process to terminate.
+pub enum Termination {
+    Sent,
+    Failed,
+    /// The process was gone by the time the signal was to be sent.
+    Exited,
+    /// The platform has no SIGTERM.
+    Unsupported,
+}
+
+/// Where the dashboard gets its readings. The live source reads the system
+/// through sysinfo, tests substitute fixed values with `TestBackend`.
+///
+/// The `refresh_*` methods fetch new data for the getters to return, and are
+/// only called for the data that is due, so sources that don't change can
+/// leave them out.
+pub trait MetricsSource {
+    fn refresh_memory(&mut self) {}
+    fn refresh_disks(&mut self) {}
+    fn refresh_cpu(&mut self) {}
+    fn refresh_processes(&mut self) {}
+    fn refresh_network(&mut self) {}
+    fn refresh_temperatures(&mut self) {}
+    fn reset_network_totals(&mut self) {}
+
+    fn memory(&self) -> MemoryStats;
+    /// The mounted filesystems, down to those `shows_mount` accepts the
+    /// mount point of.
+    fn disks(
+        &self,
+        show_pseudo_filesystems: bool,
+        shows_mount: &dyn Fn(&str) -> bool,
+    ) -> Vec<DiskStats>;
+    fn disk_rates(&self) -> &[DiskRate];
+    /// `None` until enough samples were taken for usage to mean anything.
+    fn cpu(&self) -> Option<CpuStats>;
+    fn cpu_history(&self) -> &History;
+    fn set_history_length(&mut self, length: usize);
+    fn core_count(&self) -> usize;
+    fn load(&self) -> Option<LoadStats>;
+    fn uptime(&self) -> u64;
+    fn boot_time(&self) -> u64;
+    fn temperatures(&self) -> Vec<TemperatureStats>;
+    fn network_rates(&self) -> &[InterfaceRate];
+    fn programs(&self, names: &[String]) -> Vec<ProgramStats>;
+    fn top_processes(
+        &self,
+        sort: ProcessSort,
+        count: usize,
+        filter: Option<&Regex>,
+    ) -> Vec<ProcessStats>;
+    fn process_states(&self) -> ProcessStates;
+    fn terminate(&self, pid: Pid) -> Termination;
+}
+
+/// The live system, as seen through sysinfo.
+pub struct SystemSource {
+    sys: System,
+    disks: Disks,
+    components: Components,
+    disk_io: DiskIoSampler,
+    cpu: CpuSampler,
+    network: NetworkSampler,
+}
+
+impl SystemSource {
+    pub fn new(history_length: usize) -> Self {
+        let disks = Disks::new_with_refreshed_list();
+        SystemSource {
+            sys: System::new_all(),
+            disk_io: DiskIoSampler::new(&disks),
+            disks,
+            components: Components::new_with_refreshed_list(),
+            cpu: CpuSampler::new(history_length),
+            network: NetworkSampler::new(),
+        }
+    }
+
+    /// Takes the two CPU samples that usage is worked out from, waiting in
+    /// between as long as sysinfo needs.
+    pub fn warm_up(&mut self) {
+        self.cpu.refresh(&mut self.sys);
+        std::thread::sleep(MINIMUM_CPU_UPDATE_INTERVAL);
+        self.cpu.refresh(&mut self.sys);
+    }
+}
+
+impl MetricsSource for SystemSource {
+    fn refresh_memory(&mut self) {
+        self.sys.refresh_memory();
+    }
+
+    fn refresh_disks(&mut self) {
+        self.disks.refresh(true);
+        self.disk_io.refresh(&self.disks);
+    }
+
+    fn refresh_cpu(&mut self) {
+        self.cpu.refresh(&mut self.sys);
+    }
+
+    fn refresh_processes(&mut self) {
+        self.sys
+            .refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    }
+
+    fn refresh_network(&mut self) {
+        self.network.refresh();
+    }
+
+    fn reset_network_totals(&mut self) {
+        self.network.reset_totals();
+    }
+
+    fn refresh_temperatures(&mut self) {
+        self.components.refresh(true);
+    }
+
+    fn memory(&self) -> MemoryStats {
+        collect_memory(&self.sys)
+    }
+
+    fn disks(
+        &self,
+        show_pseudo_filesystems: bool,
+        shows_mount: &dyn Fn(&str) -> bool,
+    ) -> Vec<DiskStats> {
+        collect_disks(&self.disks, show_pseudo_filesystems, shows_mount)
+    }
+
+    fn disk_rates(&self) -> &[DiskRate] {
+        &self.disk_io.rates
+    }
+
+    fn cpu(&self) -> Option<CpuStats> {
+        self.cpu
+            .warmed_up
+            .then(|| collect_cpu(&self.sys, &self.cpu))
+    }
+
+    fn cpu_history(&self) -> &History {
+        &self.cpu.history
+    }
+
+    fn set_history_length(&mut self, length: usize) {
+        self.cpu.history.set_capacity(length);
+    }
+
+    fn core_count(&self) -> usize {
+        self.sys.cpus().len()
+    }
+
+    fn load(&self) -> Option<LoadStats> {
+        collect_load()
+    }
+
+    fn uptime(&self) -> u64 {
+        collect_uptime()
+    }
+
+    fn boot_time(&self) -> u64 {
+        collect_boot_time()
+    }
+
+    fn temperatures(&self) -> Vec<TemperatureStats> {
+        collect_temperatures(&self.components)
+    }
+
+    fn network_rates(&self) -> &[InterfaceRate] {
+        &self.network.rates
+    }
+
+    fn programs(&self, names: &[String]) -> Vec<ProgramStats> {
+        collect_programs(&self.sys, names)
+    }
+
+    fn top_processes(
+        &self,
+        sort: ProcessSort,
+        count: usize,
+        filter: Option<&Regex>,
+    ) -> Vec<ProcessStats> {
+        collect_top_processes(&self.sys, sort, count, filter)
+    }
+
+    fn process_states(&self) -> ProcessStates {
+        collect_process_states(&self.sys)
+    }
+
+    fn terminate(&self, pid: Pid) -> Termination {
+        match self.sys.process(pid) {
+            None => Termination::Exited,
+            Some(process) => match process.kill_with(Signal::Term) {
+                Some(true) => Termination::Sent,
+                Some(false) => Termination::Failed,
+                None => Termination::Unsupported,
+            },
+        }
+    }
+}
+
+/// Fixed readings for tests. Every field can be changed to set up a case,
+/// the defaults describe an unremarkable, mostly idle machine.
+#[cfg(test)]
+pub struct TestBackend {
+    pub memory: MemoryStats,
+    /// Replaces `memory` once memory is refreshed, to tell when it was.
+    pub refreshed_memory: Option<MemoryStats>,
+    pub disks: Vec<DiskStats>,
+    pub disk_rates: Vec<DiskRate>,
+    pub cpu: Option<CpuStats>,
+    pub cpu_history: History,
+    pub load: Option<LoadStats>,
+    pub uptime: u64,
+    pub boot_time: u64,
+    pub temperatures: Vec<TemperatureStats>,
+    pub network_rates: Vec<InterfaceRate>,
+    /// Watched program names that count as running, with one process each.
+    pub running_programs: Vec<String>,
+    /// Already in display order, sorting is left to the test.
+    pub processes: Vec<ProcessStats>,
+    pub process_states: ProcessStates,
+}
+
+#[cfg(test)]
+impl Default for TestBackend {
+    fn default() -> Self {
+        const GIB: u64 = 1024 * 1024 * 1024;
+        TestBackend {
+            memory: MemoryStats {
+                used: 4 * GIB,
+                total: 16 * GIB,
+                available: 12 * GIB,
+                cached: Some(2 * GIB),
+                buffers: Some(GIB / 4),
+                swap_used: 0,
+                swap_total: 0,
+                swap_free: 0,
+            },
+            refreshed_memory: None,
+            disks: vec![DiskStats {
+                mount_point: "/".to_string(),
+                file_system: "ext4".to_string(),
+                used: 100 * GIB,
+                total: 400 * GIB,
+                inodes: Some(InodeStats {
+                    used: 250_000,
+                    total: 26_214_400,
+                }),
+            }],
+            disk_rates: vec![DiskRate {
+                name: "/dev/sda".to_string(),
+                read: 0.0,
+                written: 0.0,
+            }],
+            cpu: Some(CpuStats {
+                usage: 12.5,
+                cores: vec![10.0, 15.0],
+                frequencies: vec![2400, 0],
+                steal: Some(0.0),
+                throttled: None,
+            }),
+            cpu_history: History::new(60),
+            load: Some(LoadStats {
+                one: 0.5,
+                five: 0.25,
+                fifteen: 0.1,
+            }),
+            uptime: 90061,
+            boot_time: 1_700_000_000,
+            temperatures: Vec::new(),
+            network_rates: vec![InterfaceRate {
+                name: "eth0".to_string(),
+                received: 1024.0,
+                transmitted: 512.0,
+                session_received: 3 * 1024 * 1024,
+                session_transmitted: 1024 * 1024,
+                total_received: 5 * 1024 * 1024 * 1024,
+                total_transmitted: 2 * 1024 * 1024 * 1024,
+            }],
+            running_programs: Vec::new(),
+            processes: Vec::new(),
+            process_states: ProcessStates {
+                total: 120,
+                running: 2,
+                sleeping: 118,
+                stopped: 0,
+                zombie: 0,
+                threads: Some(400),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+impl MetricsSource for TestBackend {
+    fn refresh_memory(&mut self) {
+        if let Some(memory) = self.refreshed_memory.take() {
+            self.memory = memory;
+        }
+    }
+
+    fn memory(&self) -> MemoryStats {
+        self.memory.clone()
+    }
+
+    fn disks(
+        &self,
+        show_pseudo_filesystems: bool,
+        shows_mount: &dyn Fn(&str) -> bool,
+    ) -> Vec<DiskStats> {
+        self.disks
+            .iter()
+            .filter(|disk| {
+                show_pseudo_filesystems || !PSEUDO_FILESYSTEMS.contains(&disk.file_system.as_str())
+            })
+            .filter(|disk| shows_mount(&disk.mount_point))
+            .cloned()
+            .collect()
+    }
+
+    fn disk_rates(&self) -> &[DiskRate] {
+        &self.disk_rates
+    }
+
+    fn cpu(&self) -> Option<CpuStats> {
+        self.cpu.clone()
+    }
+
+    fn cpu_history(&self) -> &History {
+        &self.cpu_history
+    }
+
+    fn set_history_length(&mut self, length: usize) {
+        self.cpu_history.set_capacity(length);
+    }
+
+    fn core_count(&self) -> usize {
+        self.cpu.as_ref().map_or(1, |cpu| cpu.cores.len())
+    }
+
+    fn load(&self) -> Option<LoadStats> {
+        self.load.clone()
+    }
+
+    fn uptime(&self) -> u64 {
+        self.uptime
+    }
+
+    fn boot_time(&self) -> u64 {
+        self.boot_time
+    }
+
+    fn temperatures(&self) -> Vec<TemperatureStats> {
+        self.temperatures.clone()
+    }
+
+    fn network_rates(&self) -> &[InterfaceRate] {
+        &self.network_rates
+    }
+
+    fn programs(&self, names: &[String]) -> Vec<ProgramStats> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let running = self.running_programs.contains(name);
+                ProgramStats {
+                    name: name.clone(),
+                    processes: running as usize,
+                    first_pid: running.then(|| Pid::from(1000 + i)),
+                    cpu: 0.0,
+                    memory: 0,
+                }
+            })
+            .collect()
+    }
+
+    fn top_processes(
+        &self,
+        _sort: ProcessSort,
+        count: usize,
+        filter: Option<&Regex>,
+    ) -> Vec<ProcessStats> {
+        self.processes
+            .iter()
+            .filter(|process| filter.is_none_or(|filter| filter.is_match(&process.name)))
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    fn process_states(&self) -> ProcessStates {
+        self.process_states.clone()
+    }
+
+    fn terminate(&self, _pid: Pid) -> Termination {
+        Termination::Unsupported
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+use std::thread;
+
+use notify_rust::Notification;
+
+/// Sends a desktop notification when a reading crosses into its critical
+/// level and another when it recovers, rather than one every tick while it
+/// stays critical.
+pub struct Notifier {
+    /// Names of the readings currently at or past their critical level.
+    critical: HashSet<String>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Notifier {
+            critical: HashSet::new(),
+        }
+    }
+
+    /// Compares `usage` with `critical`, both in percent, and notifies if
+    /// `name` changed sides since the last check.
+    pub fn check(&mut self, name: &str, usage: f64, critical: f64) {
+        let is_critical = usage >= critical;
+        if is_critical == self.critical.contains(name) {
+            return;
+        }
+
+        if is_critical {
+            self.critical.insert(name.to_string());
+            send(
+                format!("{} usage is critical", name),
+                format!("{} is at {:.1}%", name, usage),
+            );
+        } else {
+            self.critical.remove(name);
+            send(
+                format!("{} usage has recovered", name),
+                format!("{} is back down to {:.1}%", name, usage),
+            );
+        }
+    }
+}
+
+fn send(summary: String, body: String) {
+    // Talking to the notification daemon can block, keep it off the UI thread.
+    // Without a daemon there is nobody to tell, so failures are dropped.
+    thread::spawn(move || {
+        let _ = Notification::new()
+            .appname("rashboard")
+            .summary(&summary)
+            .body(&body)
+            .show();
+    });
+}
@@ -0,0 +1,132 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::format::{format_bytes, format_count, format_uptime, NumberFormat};
+use crate::metrics::{CpuStats, DiskStats, LoadStats, MemoryStats};
+
+/// The dashboard's readings at one point in time, for attaching to bug
+/// reports and the like. Byte counts are in bytes, usage in percent.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub taken_at: DateTime<Local>,
+    pub hostname: Option<String>,
+    pub uptime_seconds: u64,
+    pub memory: MemoryStats,
+    /// Absent until enough samples were taken to measure usage.
+    pub cpu: Option<CpuStats>,
+    /// Absent where the OS has no load average.
+    pub load_average: Option<LoadStats>,
+    pub disks: Vec<DiskStats>,
+    /// Pending package upgrades, if they have been counted.
+    pub updates: Option<usize>,
+    pub programs: Vec<ProgramSnapshot>,
+}
+
+#[derive(Serialize)]
+pub struct ProgramSnapshot {
+    pub name: String,
+    /// "running" or "not running" for processes, the systemd state for units,
+    /// and `None` while that hasn't been fetched yet.
+    pub state: Option<String>,
+}
+
+impl Snapshot {
+    /// Writes the snapshot to a new timestamped file in the current
+    /// directory, returning its path.
+    pub fn write(&self) -> io::Result<PathBuf> {
+        let path = PathBuf::from(format!(
+            "rashboard-snapshot-{}.json",
+            self.taken_at.format("%Y%m%d-%H%M%S")
+        ));
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json + "\n")?;
+        Ok(path)
+    }
+
+    /// The readings as a short plain-text report, one per line.
+    pub fn summary(&self, units: NumberFormat) -> String {
+        let percent = |used: u64, total: u64| used as f64 / total.max(1) as f64 * 100.0;
+        let mut out = String::new();
+
+        let _ = write!(
+            out,
+            "rashboard summary at {}",
+            self.taken_at.format("%Y-%m-%d %H:%M:%S")
+        );
+        if let Some(hostname) = &self.hostname {
+            let _ = write!(out, " on {}", hostname);
+        }
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Uptime:  {}", format_uptime(self.uptime_seconds));
+
+        let memory = &self.memory;
+        let _ = writeln!(
+            out,
+            "Memory:  {} / {} ({:.1}%), {} available",
+            format_bytes(memory.used, units),
+            format_bytes(memory.total, units),
+            percent(memory.used, memory.total),
+            format_bytes(memory.available, units)
+        );
+        if memory.swap_total > 0 {
+            let _ = writeln!(
+                out,
+                "Swap:    {} / {} ({:.1}%)",
+                format_bytes(memory.swap_used, units),
+                format_bytes(memory.swap_total, units),
+                percent(memory.swap_used, memory.swap_total)
+            );
+        }
+        match &self.cpu {
+            Some(cpu) => {
+                let _ = write!(out, "CPU:     {:.1}%", cpu.usage);
+                if let Some(steal) = cpu.steal {
+                    let _ = write!(out, ", {:.1}% stolen", steal);
+                }
+                let _ = writeln!(out);
+            }
+            None => {
+                let _ = writeln!(out, "CPU:     not measured yet");
+            }
+        }
+        if let Some(load) = &self.load_average {
+            let _ = writeln!(
+                out,
+                "Load:    {:.2} {:.2} {:.2}",
+                load.one, load.five, load.fifteen
+            );
+        }
+        for disk in &self.disks {
+            let _ = writeln!(
+                out,
+                "Disk:    {} ({}) {} / {} ({:.1}%)",
+                disk.mount_point,
+                disk.file_system,
+                format_bytes(disk.used, units),
+                format_bytes(disk.total, units),
+                percent(disk.used, disk.total)
+            );
+        }
+        if let Some(updates) = self.updates {
+            let _ = writeln!(
+                out,
+                "Updates: {} available",
+                format_count(updates as u64, units)
+            );
+        }
+        for program in &self.programs {
+            let _ = writeln!(
+                out,
+                "Program: {} {}",
+                program.name,
+                program.state.as_deref().unwrap_or("unknown")
+            );
+        }
+        out
+    }
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, PanelKind};
+use crate::format::UsageFormat;
+use crate::metrics::ProcessSort;
+
+/// What was changed on the dashboard while it ran, saved on exit and put
+/// back on the next start. Kept out of the config file, which is the user's
+/// to edit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// The panels in the order they were left in.
+    pub panels: Vec<PanelKind>,
+    /// Panels toggled off.
+    pub hidden: Vec<PanelKind>,
+    pub focused: Option<PanelKind>,
+    pub process_sort: ProcessSort,
+    pub usage_format: UsageFormat,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        UiState {
+            panels: Vec::new(),
+            hidden: Vec::new(),
+            focused: None,
+            process_sort: ProcessSort::Cpu,
+            usage_format: UsageFormat::default(),
+        }
+    }
+}
+
+impl UiState {
+    /// `ui-state.json` in the state directory.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(config::state_dir()?.join("ui-state.json"))
+    }
+
+    /// The state saved at `path`, or `None` when there is none or it can't
+    /// be read, in which case the dashboard starts as configured.
+    pub fn load(path: &Path) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, json + "\n")
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tui::style::Color;
+use tui::widgets::BorderType;
+
+/// The colors the dashboard is drawn with, and its panels' borders. Draw
+/// functions take colors by role rather than naming colors themselves.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub border: Color,
+    pub focused_border: Color,
+    pub title: Color,
+    pub text: Color,
+    /// De-emphasized text, such as key hints.
+    pub muted: Color,
+    pub good: Color,
+    pub warn: Color,
+    pub critical: Color,
+    /// Unfilled part of gauges.
+    pub gauge_background: Color,
+    /// Text on a colored badge, such as the paused marker.
+    pub badge_text: Color,
+    pub borders: BorderStyle,
+    /// Color gauges along a smooth green, yellow and red gradient by how full
+    /// they are, instead of with `good`, `warn` and `critical`.
+    pub gradient: bool,
+}
+
+/// Whether gauges are drawn with a gradient, which takes a terminal with
+/// 24-bit color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Gradient {
+    /// When `COLORTERM` says the terminal has 24-bit color.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Gradient {
+    pub fn enabled(self) -> bool {
+        match self {
+            Gradient::Auto => std::env::var("COLORTERM")
+                .is_ok_and(|colorterm| matches!(colorterm.as_str(), "truecolor" | "24bit")),
+            Gradient::Always => true,
+            Gradient::Never => false,
+        }
+    }
+}
+
+/// How panels are framed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    Plain,
+    Rounded,
+    Thick,
+    Double,
+    /// No frame, only the title line, for a denser look.
+    None,
+}
+
+impl BorderStyle {
+    /// The line drawing to frame panels with, `None` for no frame at all.
+    pub fn border_type(self) -> Option<BorderType> {
+        match self {
+            BorderStyle::Plain => Some(BorderType::Plain),
+            BorderStyle::Rounded => Some(BorderType::Rounded),
+            BorderStyle::Thick => Some(BorderType::Thick),
+            BorderStyle::Double => Some(BorderType::Double),
+            BorderStyle::None => None,
+        }
+    }
+}
+
+/// The built-in themes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    Dark,
+    Light,
+    Solarized,
+    /// Blue, yellow and orange in place of green, yellow and red, told apart
+    /// with any kind of color blindness.
+    ColorBlind,
+}
+
+impl ThemeName {
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::Dark => Theme {
+                border: Color::Reset,
+                focused_border: Color::LightYellow,
+                title: Color::Reset,
+                text: Color::Reset,
+                muted: Color::DarkGray,
+                good: Color::Green,
+                warn: Color::Yellow,
+                critical: Color::Red,
+                gauge_background: Color::Black,
+                badge_text: Color::Black,
+                borders: BorderStyle::Plain,
+                gradient: false,
+            },
+            ThemeName::Light => Theme {
+                border: Color::Gray,
+                focused_border: Color::Blue,
+                title: Color::Black,
+                text: Color::Black,
+                muted: Color::Gray,
+                good: Color::Rgb(0, 128, 0),
+                warn: Color::Rgb(175, 95, 0),
+                critical: Color::Rgb(190, 0, 0),
+                gauge_background: Color::Rgb(220, 220, 220),
+                badge_text: Color::White,
+                borders: BorderStyle::Plain,
+                gradient: false,
+            },
+            // https://ethanschoonover.com/solarized/
+            ThemeName::Solarized => Theme {
+                border: Color::Rgb(88, 110, 117),
+                focused_border: Color::Rgb(38, 139, 210),
+                title: Color::Rgb(147, 161, 161),
+                text: Color::Rgb(131, 148, 150),
+                muted: Color::Rgb(88, 110, 117),
+                good: Color::Rgb(133, 153, 0),
+                warn: Color::Rgb(181, 137, 0),
+                critical: Color::Rgb(220, 50, 47),
+                gauge_background: Color::Rgb(7, 54, 66),
+                badge_text: Color::Rgb(0, 43, 54),
+                borders: BorderStyle::Plain,
+                gradient: false,
+            },
+            // From the Okabe-Ito palette, https://jfly.uni-koeln.de/color/
+            ThemeName::ColorBlind => Theme {
+                border: Color::Reset,
+                focused_border: Color::Rgb(86, 180, 233),
+                title: Color::Reset,
+                text: Color::Reset,
+                muted: Color::DarkGray,
+                good: Color::Rgb(86, 180, 233),
+                warn: Color::Rgb(240, 228, 66),
+                critical: Color::Rgb(213, 94, 0),
+                gauge_background: Color::Black,
+                badge_text: Color::Black,
+                borders: BorderStyle::Plain,
+                gradient: false,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    UnknownRole(PathBuf, String),
+    InvalidColor {
+        path: PathBuf,
+        role: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(path, err) => write!(f, "failed to read {}: {}", path.display(), err),
+            ThemeError::Parse(path, err) => {
+                write!(f, "invalid theme {}:\n{}", path.display(), err)
+            }
+            ThemeError::UnknownRole(path, role) => {
+                write!(
+                    f,
+                    "invalid theme {}: unknown color `{}`",
+                    path.display(),
+                    role
+                )
+            }
+            ThemeError::InvalidColor { path, role, value } => write!(
+                f,
+                "invalid theme {}: `{}` for `{}` is not a color name or #rrggbb",
+                path.display(),
+                value,
+                role
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl Theme {
+    /// Reads a palette file mapping roles to colors, e.g. `warn = "#b58900"`.
+    /// Roles the file leaves out keep their color from `base`.
+    pub fn from_file(path: &Path, base: Theme) -> Result<Theme, ThemeError> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| ThemeError::Io(path.to_path_buf(), err))?;
+        let colors: BTreeMap<String, String> =
+            toml::from_str(&contents).map_err(|err| ThemeError::Parse(path.to_path_buf(), err))?;
+
+        let mut theme = base;
+        for (role, value) in colors {
+            let Some(color) = parse_color(&value) else {
+                return Err(ThemeError::InvalidColor {
+                    path: path.to_path_buf(),
+                    role,
+                    value,
+                });
+            };
+            let slot = match role.as_str() {
+                "border" => &mut theme.border,
+                "focused_border" => &mut theme.focused_border,
+                "title" => &mut theme.title,
+                "text" => &mut theme.text,
+                "muted" => &mut theme.muted,
+                "good" => &mut theme.good,
+                "warn" => &mut theme.warn,
+                "critical" => &mut theme.critical,
+                "gauge_background" => &mut theme.gauge_background,
+                "badge_text" => &mut theme.badge_text,
+                _ => return Err(ThemeError::UnknownRole(path.to_path_buf(), role)),
+            };
+            *slot = color;
+        }
+        Ok(theme)
+    }
+}
+
+/// Parses one of the terminal's named colors, or `#rrggbb` for truecolor.
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let color = match value.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
@@ -0,0 +1,179 @@
+use std::process::Command;
+
+use regex::Regex;
+
+/// Package managers whose pending upgrades can be counted.
+#[derive(Clone, Copy)]
+pub enum PackageManager {
+    Apt,
+    Dnf,
+    Pacman,
+    Zypper,
+}
+
+impl PackageManager {
+    const ALL: [PackageManager; 4] = [
+        PackageManager::Apt,
+        PackageManager::Dnf,
+        PackageManager::Pacman,
+        PackageManager::Zypper,
+    ];
+
+    /// Picks the first package manager whose binary is on the `PATH`.
+    pub fn detect() -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|manager| is_on_path(manager.binary()))
+    }
+
+    fn binary(self) -> &'static str {
+        match self {
+            PackageManager::Apt => "apt",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Pacman => "pacman",
+            PackageManager::Zypper => "zypper",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PackageManager::Apt => "Apt",
+            PackageManager::Dnf => "Dnf",
+            PackageManager::Pacman => "Pacman",
+            PackageManager::Zypper => "Zypper",
+        }
+    }
+
+    /// Command listing upgradable packages, one per line.
+    fn list_command(self) -> Command {
+        let mut command = Command::new(self.binary());
+        match self {
+            PackageManager::Apt => command.args(["list", "--upgradable"]),
+            PackageManager::Dnf => command.args(["-q", "check-update"]),
+            PackageManager::Pacman => command.arg("-Qu"),
+            PackageManager::Zypper => command.args(["-q", "list-updates"]),
+        };
+        command
+    }
+
+    /// Pattern matching a single upgradable package line in the listing,
+    /// capturing the package's `name`, the version it goes `to` and, where
+    /// the listing says, the one it comes `from`.
+    fn update_pattern(self) -> &'static str {
+        match self {
+            // bash/jammy-updates 5.1-6ubuntu1.1 amd64 [upgradable from: 5.1-6ubuntu1]
+            PackageManager::Apt => {
+                r"^(?P<name>[^\s/]+)/\S+\s+(?P<to>\S+)\s+\S+\s+\[upgradable from: (?P<from>[^\]]+)\]"
+            }
+            // bash.x86_64  5.2.26-3.fc40  updates
+            PackageManager::Dnf => r"^(?P<name>\S+)\.\S+\s+(?P<to>\S+)\s+\S+$",
+            // bash 5.2.026-2 -> 5.2.026-3
+            PackageManager::Pacman => r"^(?P<name>\S+) (?P<from>\S+) -> (?P<to>\S+)",
+            // v | repo-oss | bash | 5.2.15-2.1 | 5.2.15-2.2 | x86_64
+            PackageManager::Zypper => {
+                r"^v\s+\|[^|]*\|\s*(?P<name>[^|\s]+)\s*\|\s*(?P<from>[^|\s]+)\s*\|\s*(?P<to>[^|\s]+)"
+            }
+        }
+    }
+
+    /// Whether an exit code means the listing succeeded. dnf exits 100 when
+    /// updates are available and pacman exits 1 when there are none.
+    fn is_success(self, code: Option<i32>) -> bool {
+        match self {
+            PackageManager::Apt | PackageManager::Zypper => code == Some(0),
+            PackageManager::Dnf => matches!(code, Some(0 | 100)),
+            PackageManager::Pacman => matches!(code, Some(0 | 1)),
+        }
+    }
+
+    /// Lists upgradable packages, or says why the listing couldn't be run.
+    pub fn list_updates(self) -> Result<Vec<PackageUpdate>, String> {
+        let output = self
+            .list_command()
+            .output()
+            .map_err(|err| format!("Failed to execute {}: {}", self.binary(), err))?;
+        if !self.is_success(output.status.code()) {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "{} failed ({}): {}",
+                self.binary(),
+                output.status,
+                stderr.trim()
+            ));
+        }
+
+        Ok(self.parse_updates(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn parse_updates(self, listing: &str) -> Vec<PackageUpdate> {
+        let pattern = Regex::new(self.update_pattern()).expect("invalid update pattern");
+        listing
+            .lines()
+            .filter_map(|line| {
+                let captures = pattern.captures(line)?;
+                let capture = |name| captures.name(name).map(|m| m.as_str().to_string());
+                Some(PackageUpdate {
+                    name: capture("name")?,
+                    from: capture("from"),
+                    to: capture("to")?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A package waiting to be upgraded.
+#[derive(Debug, PartialEq)]
+pub struct PackageUpdate {
+    pub name: String,
+    /// The installed version, where the package manager lists it.
+    pub from: Option<String>,
+    pub to: String,
+}
+
+fn is_on_path(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgradable_packages_are_listed_with_their_versions() {
+        let listing = "\
+Listing... Done
+bash/jammy-updates 5.1-6ubuntu1.1 amd64 [upgradable from: 5.1-6ubuntu1]
+libc6/jammy-security 2.35-0ubuntu3.8 amd64 [upgradable from: 2.35-0ubuntu3.7]
+";
+        assert_eq!(
+            PackageManager::Apt.parse_updates(listing),
+            [
+                PackageUpdate {
+                    name: "bash".to_string(),
+                    from: Some("5.1-6ubuntu1".to_string()),
+                    to: "5.1-6ubuntu1.1".to_string(),
+                },
+                PackageUpdate {
+                    name: "libc6".to_string(),
+                    from: Some("2.35-0ubuntu3.7".to_string()),
+                    to: "2.35-0ubuntu3.8".to_string(),
+                },
+            ]
+        );
+
+        let listing = "v | repo-oss | bash | 5.2.15-2.1 | 5.2.15-2.2 | x86_64\n";
+        let updates = PackageManager::Zypper.parse_updates(listing);
+        assert_eq!(updates[0].name, "bash");
+        assert_eq!(updates[0].from.as_deref(), Some("5.2.15-2.1"));
+        assert_eq!(updates[0].to, "5.2.15-2.2");
+
+        let updates = PackageManager::Dnf.parse_updates("bash.x86_64  5.2.26-3.fc40  updates\n");
+        assert_eq!(
+            (updates[0].name.as_str(), updates[0].from.as_ref()),
+            ("bash", None)
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::alerts::{Alert, Level};
+use crate::config::{Webhook, WebhookFormat};
+
+/// Tries per alert before giving up on it.
+const ATTEMPTS: u32 = 3;
+
+/// How long a single request may take.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Alerts waiting to be posted before more are dropped, so a flapping
+/// reading against a dead endpoint can't pile them up without end.
+const QUEUE_LENGTH: usize = 32;
+
+/// An alert on its way to the webhook.
+struct Delivery {
+    url: String,
+    payload: serde_json::Value,
+}
+
+/// Posts alerts to the configured webhook. They are sent one after another
+/// from a worker thread, so a slow or unreachable endpoint never holds up
+/// the dashboard, and the ones that still fail after retrying, or don't fit
+/// in the queue, are handed back through `failures`.
+pub struct WebhookSender {
+    queue: SyncSender<Delivery>,
+    failures: Sender<String>,
+    rx: Receiver<String>,
+}
+
+impl WebhookSender {
+    pub fn new() -> Self {
+        let (queue, deliveries) = mpsc::sync_channel(QUEUE_LENGTH);
+        let (failures, rx) = mpsc::channel();
+        let tx = failures.clone();
+        // Ends once the sender, and with it the queue, is dropped
+        thread::spawn(move || {
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .timeout_global(Some(TIMEOUT))
+                .build()
+                .into();
+            for delivery in deliveries {
+                if let Err(err) = deliver(&agent, &delivery) {
+                    // The receiver is gone once the app is shutting down
+                    let _ = tx.send(err);
+                }
+            }
+        });
+        WebhookSender {
+            queue,
+            failures,
+            rx,
+        }
+    }
+
+    /// Sends `alert` about `host` if the webhook is configured for its kind.
+    pub fn send(&self, webhook: &Webhook, alert: &Alert, host: Option<&str>) {
+        if !webhook.alerts.contains(&alert.kind) {
+            return;
+        }
+
+        let delivery = Delivery {
+            url: webhook.url.clone(),
+            payload: payload(webhook.format, alert, host),
+        };
+        if let Err(TrySendError::Full(_)) = self.queue.try_send(delivery) {
+            let _ = self.failures.send(format!(
+                "too many alerts waiting to be posted, dropped the one about {}",
+                alert.name
+            ));
+        }
+    }
+
+    /// Why alerts couldn't be delivered, since the last call.
+    pub fn failures(&self) -> impl Iterator<Item = String> + '_ {
+        self.rx.try_iter()
+    }
+}
+
+/// Posts one alert, retrying a few times before giving up on it.
+fn deliver(agent: &ureq::Agent, delivery: &Delivery) -> Result<(), String> {
+    let mut attempt = 1;
+    loop {
+        match agent.post(&delivery.url).send_json(&delivery.payload) {
+            Ok(_) => return Ok(()),
+            Err(err) if attempt == ATTEMPTS => {
+                return Err(format!("{} (after {} tries)", err, ATTEMPTS));
+            }
+            // Backing off a little more each time
+            Err(_) => thread::sleep(Duration::from_secs(u64::from(attempt))),
+        }
+        attempt += 1;
+    }
+}
+
+/// The JSON body posted for `alert`, in the shape `format` asks for.
+fn payload(format: WebhookFormat, alert: &Alert, host: Option<&str>) -> serde_json::Value {
+    match format {
+        WebhookFormat::Generic => json!({
+            "host": host,
+            "time": alert.time.to_rfc3339(),
+            "kind": alert.kind,
+            "metric": alert.name,
+            "level": alert.level,
+            "value": alert.usage,
+        }),
+        // Slack and the services copying its incoming webhooks show `text`
+        WebhookFormat::Slack => {
+            let event = match alert.level {
+                Level::Critical => "is critical",
+                Level::Warn => "is high",
+                Level::Normal => "has recovered",
+            };
+            let text = format!(
+                "{}: {} {} at {:.1}%",
+                host.unwrap_or("rashboard"),
+                alert.name,
+                event,
+                alert.usage
+            );
+            json!({ "text": text })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::alerts::AlertKind;
+
+    #[test]
+    fn webhook_payloads_carry_the_alert() {
+        let alert = Alert {
+            time: chrono::Local
+                .with_ymd_and_hms(2024, 5, 1, 12, 30, 0)
+                .unwrap(),
+            kind: AlertKind::Disk,
+            name: "Disk /home".to_string(),
+            level: Level::Critical,
+            usage: 96.5,
+        };
+
+        let generic = payload(WebhookFormat::Generic, &alert, Some("web1"));
+        assert_eq!(generic["host"], "web1");
+        assert_eq!(generic["time"], alert.time.to_rfc3339());
+        assert_eq!(generic["kind"], "disk");
+        assert_eq!(generic["metric"], "Disk /home");
+        assert_eq!(generic["level"], "critical");
+        assert_eq!(generic["value"], 96.5);
+
+        let slack = payload(WebhookFormat::Slack, &alert, None);
+        assert_eq!(
+            slack,
+            serde_json::json!({ "text": "rashboard: Disk /home is critical at 96.5%" })
+        );
+    }
+}